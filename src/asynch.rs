@@ -0,0 +1,292 @@
+//! Async driver built on `embedded-hal-async`.
+
+use crate::instruction::Instruction;
+use crate::{Orientation, DEFAULT_INIT_SEQUENCE};
+
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+/// Async ST7735 driver to connect to TFT displays.
+pub struct AsyncST7735<SPI, DC, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    /// SPI
+    spi: SPI,
+
+    /// Data/command pin.
+    dc: DC,
+
+    /// Reset pin.
+    rst: RST,
+
+    /// Whether the display is RGB (true) or BGR (false)
+    rgb: bool,
+
+    /// Whether the colours are inverted (true) or not (false)
+    inverted: bool,
+
+    /// Current orientation
+    orientation: Orientation,
+
+    /// Global image offset, in portrait orientation
+    dx: u16,
+    dy: u16,
+    width: u32,
+    height: u32,
+}
+
+impl<SPI, DC, RST> AsyncST7735<SPI, DC, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    /// Creates a new driver instance that uses an async SPI device.
+    pub fn new(
+        spi: SPI,
+        dc: DC,
+        rst: RST,
+        rgb: bool,
+        inverted: bool,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        AsyncST7735 {
+            spi,
+            dc,
+            rst,
+            rgb,
+            inverted,
+            orientation: Orientation::Portrait,
+            dx: 0,
+            dy: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Runs commands to initialize the display, see [`DEFAULT_INIT_SEQUENCE`].
+    pub async fn init<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayNs,
+    {
+        self.hard_reset(delay).await?;
+        for &(command, params, delay_ms) in DEFAULT_INIT_SEQUENCE {
+            self.write_command(command, params).await?;
+            if delay_ms > 0 {
+                delay.delay_ms(u32::from(delay_ms)).await;
+            }
+        }
+        if self.inverted {
+            self.write_command(Instruction::INVON, &[]).await?;
+        } else {
+            self.write_command(Instruction::INVOFF, &[]).await?;
+        }
+        self.write_command(Instruction::MADCTL, &[self.madctl()])
+            .await?;
+        self.write_command(Instruction::COLMOD, &[0x05]).await?;
+        self.write_command(Instruction::DISPON, &[]).await?;
+        delay.delay_ms(200).await;
+        Ok(())
+    }
+
+    pub async fn hard_reset<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayNs,
+    {
+        self.rst.set_high().map_err(|_| ())?;
+        delay.delay_ms(10).await;
+        self.rst.set_low().map_err(|_| ())?;
+        delay.delay_ms(10).await;
+        self.rst.set_high().map_err(|_| ())
+    }
+
+    async fn write_command(&mut self, command: Instruction, params: &[u8]) -> Result<(), ()> {
+        self.dc.set_low().map_err(|_| ())?;
+        self.spi.write(&[command as u8]).await.map_err(|_| ())?;
+        if !params.is_empty() {
+            self.start_data()?;
+            self.write_data(params).await?;
+        }
+        Ok(())
+    }
+
+    fn start_data(&mut self) -> Result<(), ()> {
+        self.dc.set_high().map_err(|_| ())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), ()> {
+        self.spi.write(data).await.map_err(|_| ())
+    }
+
+    /// Sets the display orientation.
+    ///
+    /// Switching between portrait and landscape swaps the display width and height,
+    /// and the offsets with them.
+    pub async fn set_orientation(&mut self, orientation: &Orientation) -> Result<(), ()> {
+        if orientation.is_landscape() != self.orientation.is_landscape() {
+            core::mem::swap(&mut self.width, &mut self.height);
+        }
+        self.orientation = *orientation;
+        self.write_command(Instruction::MADCTL, &[self.madctl()])
+            .await
+    }
+
+    /// Returns the current display orientation.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns the memory access control value for the current orientation and color
+    /// order.
+    fn madctl(&self) -> u8 {
+        if self.rgb {
+            self.orientation as u8
+        } else {
+            self.orientation as u8 | 0x08
+        }
+    }
+
+    /// Sets the global offset of the displayed image
+    ///
+    /// The offset is given in portrait orientation and swapped in landscape.
+    pub fn set_offset(&mut self, dx: u16, dy: u16) {
+        self.dx = dx;
+        self.dy = dy;
+    }
+
+    /// Returns the display size in pixels, in the current orientation.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Sets the address window for the display.
+    ///
+    /// Returns an error if the offset pushes the window past the largest coordinate.
+    pub async fn set_address_window(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+    ) -> Result<(), ()> {
+        let (dx, dy) = if self.orientation.is_landscape() {
+            (self.dy, self.dx)
+        } else {
+            (self.dx, self.dy)
+        };
+        let offset = |value: u16, by: u16| value.checked_add(by).ok_or(());
+        let (sx, ex) = (offset(sx, dx)?, offset(ex, dx)?);
+        let (sy, ey) = (offset(sy, dy)?, offset(ey, dy)?);
+        let [sx0, sx1] = sx.to_be_bytes();
+        let [ex0, ex1] = ex.to_be_bytes();
+        self.write_command(Instruction::CASET, &[sx0, sx1, ex0, ex1])
+            .await?;
+        let [sy0, sy1] = sy.to_be_bytes();
+        let [ey0, ey1] = ey.to_be_bytes();
+        self.write_command(Instruction::RASET, &[sy0, sy1, ey0, ey1])
+            .await
+    }
+
+    /// Sets a pixel color at the given coords.
+    pub async fn set_pixel(&mut self, x: u16, y: u16, color: u16) -> Result<(), ()> {
+        self.set_address_window(x, y, x, y).await?;
+        self.write_command(Instruction::RAMWR, &color.to_be_bytes())
+            .await
+    }
+
+    /// Writes pixel colors sequentially into the current drawing window
+    pub async fn write_pixels<P: IntoIterator<Item = u16>>(&mut self, colors: P) -> Result<(), ()> {
+        self.write_pixels_with_buffer(colors, &mut [0; 64]).await
+    }
+
+    /// Writes pixel colors sequentially into the current drawing window, using `buffer`
+    /// to collect the encoded data before each SPI transfer.
+    ///
+    /// A larger buffer means fewer, larger transfers, which lets DMA capable SPI
+    /// devices work longer without the core. Returns an error if the buffer is
+    /// shorter than two bytes.
+    pub async fn write_pixels_with_buffer<P: IntoIterator<Item = u16>>(
+        &mut self,
+        colors: P,
+        buffer: &mut [u8],
+    ) -> Result<(), ()> {
+        if buffer.len() < 2 {
+            return Err(());
+        }
+        self.write_command(Instruction::RAMWR, &[]).await?;
+        self.start_data()?;
+        let mut index = 0;
+        for color in colors {
+            if index + 2 > buffer.len() {
+                self.write_data(&buffer[..index]).await?;
+                index = 0;
+            }
+            buffer[index..index + 2].copy_from_slice(&color.to_be_bytes());
+            index += 2;
+        }
+        if index > 0 {
+            self.write_data(&buffer[..index]).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes already encoded big-endian RGB565 data into the current drawing window.
+    ///
+    /// The whole slice is handed to the SPI device in a single transfer, which lets
+    /// DMA capable implementations send large frames without involving the core.
+    pub async fn write_pixel_data(&mut self, data: &[u8]) -> Result<(), ()> {
+        self.write_command(Instruction::RAMWR, &[]).await?;
+        self.start_data()?;
+        self.write_data(data).await
+    }
+
+    /// Sets pixel colors at the given drawing window
+    pub async fn set_pixels<P: IntoIterator<Item = u16>>(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+        colors: P,
+    ) -> Result<(), ()> {
+        self.set_address_window(sx, sy, ex, ey).await?;
+        self.write_pixels(colors).await
+    }
+
+    /// Sets pixel colors at the given drawing window, using `buffer` to collect the
+    /// encoded data before each SPI transfer.
+    pub async fn set_pixels_with_buffer<P: IntoIterator<Item = u16>>(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+        colors: P,
+        buffer: &mut [u8],
+    ) -> Result<(), ()> {
+        self.set_address_window(sx, sy, ex, ey).await?;
+        self.write_pixels_with_buffer(colors, buffer).await
+    }
+
+    /// Fills the whole display with a single color.
+    pub async fn clear(&mut self, color: u16) -> Result<(), ()> {
+        let (width, height) = (self.width as u16, self.height as u16);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.set_pixels_with_buffer(
+            0,
+            0,
+            width - 1,
+            height - 1,
+            core::iter::repeat(color).take(width as usize * height as usize),
+            &mut [0; 512],
+        )
+        .await
+    }
+}
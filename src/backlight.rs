@@ -0,0 +1,74 @@
+//! Backlight control.
+
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::PwmPin;
+
+/// A display backlight that can be switched or dimmed.
+pub trait Backlight {
+    /// Sets the backlight brightness, where 0 is off and 255 is full brightness.
+    fn set_brightness(&mut self, level: u8) -> Result<(), ()>;
+
+    /// Returns whether the backlight can be switched on and off.
+    fn is_switchable(&self) -> bool {
+        true
+    }
+
+    /// Returns whether brightness levels between off and full are shown as such.
+    fn is_dimmable(&self) -> bool {
+        false
+    }
+}
+
+/// Placeholder for displays without a controllable backlight.
+pub struct NoBacklight;
+
+impl Backlight for NoBacklight {
+    fn set_brightness(&mut self, _level: u8) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn is_switchable(&self) -> bool {
+        false
+    }
+}
+
+/// Backlight driven by an active-high output pin.
+///
+/// Any non-zero brightness turns the backlight fully on.
+pub struct PinBacklight<P>(pub P);
+
+impl<P> Backlight for PinBacklight<P>
+where
+    P: OutputPin,
+{
+    fn set_brightness(&mut self, level: u8) -> Result<(), ()> {
+        if level == 0 {
+            self.0.set_low().map_err(|_| ())
+        } else {
+            self.0.set_high().map_err(|_| ())
+        }
+    }
+}
+
+/// Backlight driven by a PWM channel, dimmed by scaling the duty cycle.
+pub struct PwmBacklight<P>(pub P);
+
+impl<P> Backlight for PwmBacklight<P>
+where
+    P: PwmPin<Duty = u16>,
+{
+    fn set_brightness(&mut self, level: u8) -> Result<(), ()> {
+        if level == 0 {
+            self.0.disable();
+        } else {
+            let duty = u32::from(self.0.get_max_duty()) * u32::from(level) / 255;
+            self.0.set_duty(duty as u16);
+            self.0.enable();
+        }
+        Ok(())
+    }
+
+    fn is_dimmable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,77 @@
+//! Alpha blending of RGB565 colors.
+
+/// How two colors are mixed by [`blend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Mixes the encoded RGB565 channel values directly. Fast, but mid-tones come out
+    /// too dark, which makes anti-aliased edges look ragged.
+    Naive,
+    /// Converts the channels to linear light, mixes them and converts back to sRGB.
+    Srgb,
+}
+
+/// Blends `fg` over `bg` with the given opacity, where 0 keeps `bg` and 255 gives `fg`.
+pub fn blend(fg: u16, bg: u16, alpha: u8, mode: BlendMode) -> u16 {
+    let (fr, fg_, fb) = split(fg);
+    let (br, bg_, bb) = split(bg);
+    let (r, g, b) = match mode {
+        BlendMode::Naive => (mix(fr, br, alpha), mix(fg_, bg_, alpha), mix(fb, bb, alpha)),
+        BlendMode::Srgb => (
+            encode(
+                mix(LINEAR_5[fr as usize], LINEAR_5[br as usize], alpha),
+                &THRESHOLD_5,
+            ),
+            encode(
+                mix(LINEAR_6[fg_ as usize], LINEAR_6[bg_ as usize], alpha),
+                &THRESHOLD_6,
+            ),
+            encode(
+                mix(LINEAR_5[fb as usize], LINEAR_5[bb as usize], alpha),
+                &THRESHOLD_5,
+            ),
+        ),
+    };
+    (r << 11) | (g << 5) | b
+}
+
+fn split(color: u16) -> (u16, u16, u16) {
+    (color >> 11, (color >> 5) & 0x3F, color & 0x1F)
+}
+
+fn mix(a: u16, b: u16, alpha: u8) -> u16 {
+    let alpha = u32::from(alpha);
+    ((u32::from(a) * alpha + u32::from(b) * (255 - alpha) + 127) / 255) as u16
+}
+
+/// Converts a linear value back to the nearest sRGB encoded channel value.
+fn encode(linear: u16, thresholds: &[u16]) -> u16 {
+    thresholds.partition_point(|&t| t <= linear) as u16
+}
+
+const LINEAR_5: [u16; 32] = [
+    0, 164, 352, 625, 992, 1461, 2040, 2734, 3550, 4492, 5565, 6775, 8127, 9623, 11269, 13069,
+    15026, 17143, 19426, 21877, 24499, 27295, 30270, 33426, 36766, 40292, 44009, 47918, 52022,
+    56325, 60828, 65535,
+];
+
+const LINEAR_6: [u16; 64] = [
+    0, 81, 161, 244, 345, 466, 609, 776, 965, 1180, 1420, 1687, 1980, 2301, 2651, 3030, 3439, 3879,
+    4349, 4851, 5386, 5953, 6554, 7189, 7858, 8562, 9302, 10078, 10890, 11739, 12626, 13551, 14513,
+    15515, 16556, 17636, 18757, 19918, 21120, 22363, 23648, 24974, 26344, 27756, 29211, 30710,
+    32253, 33840, 35471, 37148, 38870, 40638, 42452, 44312, 46219, 48173, 50174, 52223, 54320,
+    56465, 58659, 60901, 63193, 65535,
+];
+
+const THRESHOLD_5: [u16; 31] = [
+    82, 249, 477, 796, 1213, 1737, 2372, 3126, 4005, 5012, 6153, 7433, 8856, 10427, 12149, 14027,
+    16064, 18264, 20630, 23166, 25875, 28760, 31825, 35073, 38505, 42127, 45939, 49946, 54149,
+    58551, 63156,
+];
+
+const THRESHOLD_6: [u16; 63] = [
+    40, 121, 201, 292, 403, 535, 690, 868, 1070, 1297, 1550, 1830, 2137, 2473, 2837, 3231, 3655,
+    4110, 4596, 5115, 5665, 6250, 6867, 7519, 8206, 8928, 9686, 10480, 11310, 12178, 13084, 14027,
+    15009, 16031, 17091, 18192, 19332, 20514, 21736, 23000, 24306, 25654, 27044, 28478, 29955,
+    31476, 33041, 34650, 36304, 38004, 39748, 41539, 43376, 45259, 47190, 49167, 51192, 53265,
+    55386, 57556, 59774, 62041, 64358,
+];
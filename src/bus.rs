@@ -0,0 +1,206 @@
+//! Sharing one SPI bus with other displays and devices.
+
+use crate::ST7735;
+
+use core::cell::RefCell;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// A device on a shared SPI bus, selected by its own active-low chip select pin.
+///
+/// Chip select is asserted around every write, which the ST7735 supports between
+/// any two bytes. Reading back needs chip select held across a command and its
+/// response, so use a dedicated bus for displays that are read from.
+pub struct SharedSpi<'a, SPI, CS> {
+    bus: &'a RefCell<SPI>,
+    cs: CS,
+}
+
+impl<'a, SPI, CS> SharedSpi<'a, SPI, CS>
+where
+    CS: OutputPin,
+{
+    /// Creates a device on `bus` using the given chip select pin.
+    pub fn new(bus: &'a RefCell<SPI>, cs: CS) -> Self {
+        SharedSpi { bus, cs }
+    }
+
+    /// Returns the chip select pin.
+    pub fn release(self) -> CS {
+        self.cs
+    }
+}
+
+impl<SPI, CS> spi::Write<u8> for SharedSpi<'_, SPI, CS>
+where
+    SPI: spi::Write<u8>,
+    CS: OutputPin,
+{
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut bus = self.bus.borrow_mut();
+        self.cs.set_low().map_err(|_| ())?;
+        let result = bus.write(words).map_err(|_| ());
+        self.cs.set_high().map_err(|_| ())?;
+        result
+    }
+}
+
+/// An SPI bus that pauses after each write to limit how much of the bus time the
+/// display takes.
+///
+/// Wrap a [`SharedSpi`] in this to leave other devices behind the same bus room
+/// between writes. The driver splits pixel data into writes of at most the size set
+/// with [`ST7735::set_max_transfer_size`], which bounds how long others wait.
+pub struct Throttled<SPI, DELAY> {
+    spi: SPI,
+    delay: DELAY,
+    spi_hz: u32,
+    duty_percent: u8,
+}
+
+impl<SPI, DELAY> Throttled<SPI, DELAY>
+where
+    SPI: spi::Write<u8>,
+    DELAY: DelayUs<u32>,
+{
+    /// Limits `spi`, clocked at `spi_hz`, to using the bus `duty_percent` of the time.
+    ///
+    /// A duty cycle of 0 is treated as 1 percent, and 100 or more disables the limit.
+    pub fn new(spi: SPI, delay: DELAY, spi_hz: u32, duty_percent: u8) -> Self {
+        Throttled {
+            spi,
+            delay,
+            spi_hz,
+            duty_percent: duty_percent.max(1),
+        }
+    }
+
+    /// Changes the share of bus time, see [`new`](Self::new).
+    pub fn set_duty_percent(&mut self, duty_percent: u8) {
+        self.duty_percent = duty_percent.max(1);
+    }
+
+    /// Returns the bus and delay.
+    pub fn release(self) -> (SPI, DELAY) {
+        (self.spi, self.delay)
+    }
+}
+
+impl<SPI, DELAY> spi::Write<u8> for Throttled<SPI, DELAY>
+where
+    SPI: spi::Write<u8>,
+    DELAY: DelayUs<u32>,
+{
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.spi.write(words).map_err(|_| ())?;
+        if self.duty_percent < 100 && self.spi_hz > 0 {
+            let busy_us = words.len() as u64 * 8 * 1_000_000 / u64::from(self.spi_hz);
+            let idle_percent = u64::from(100 - self.duty_percent);
+            let pause_us = busy_us * idle_percent / u64::from(self.duty_percent);
+            if pause_us > 0 {
+                self.delay
+                    .delay_us(pause_us.min(u64::from(u32::MAX)) as u32);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, SPI, CS, DC, RST> ST7735<SharedSpi<'a, SPI, CS>, DC, RST>
+where
+    SPI: spi::Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    /// Creates a new driver instance for a display on a shared SPI bus, selected by
+    /// its own chip select pin.
+    ///
+    /// Displays sharing a bus can also share a reset pin by passing a pin that is
+    /// only driven by one of them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_cs(
+        bus: &'a RefCell<SPI>,
+        cs: CS,
+        dc: DC,
+        rst: RST,
+        rgb: bool,
+        inverted: bool,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        ST7735::new(
+            SharedSpi::new(bus, cs),
+            dc,
+            rst,
+            rgb,
+            inverted,
+            width,
+            height,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::mock::Recorder;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use embedded_hal::blocking::spi::Write;
+
+    /// Records the requested pauses.
+    #[derive(Default)]
+    struct Pauses(Vec<u32>);
+
+    impl DelayUs<u32> for Pauses {
+        fn delay_us(&mut self, us: u32) {
+            self.0.push(us);
+        }
+    }
+
+    fn pauses(spi_hz: u32, duty_percent: u8, writes: &[usize]) -> Vec<u32> {
+        let recorder = Recorder::new();
+        let mut spi = Throttled::new(recorder.spi(), Pauses::default(), spi_hz, duty_percent);
+        for &len in writes {
+            spi.write(&vec![0; len]).unwrap();
+        }
+        spi.release().1 .0
+    }
+
+    #[test]
+    fn pause_scales_with_the_idle_share() {
+        // 125 bytes at 1 MHz keep the bus busy for 1 ms
+        assert_eq!(pauses(1_000_000, 50, &[125]), vec![1000]);
+        assert_eq!(pauses(1_000_000, 25, &[125]), vec![3000]);
+        assert_eq!(pauses(1_000_000, 75, &[125, 250]), vec![333, 666]);
+    }
+
+    #[test]
+    fn duty_cycle_limits() {
+        assert_eq!(pauses(1_000_000, 0, &[125]), vec![99_000]);
+        assert_eq!(pauses(1_000_000, 100, &[125]), vec![]);
+        assert_eq!(pauses(1_000_000, 200, &[125]), vec![]);
+        assert_eq!(pauses(0, 50, &[125]), vec![]);
+    }
+
+    #[test]
+    fn short_writes_may_not_pause() {
+        assert_eq!(pauses(16_000_000, 50, &[1]), vec![]);
+        assert_eq!(pauses(16_000_000, 50, &[2]), vec![1]);
+    }
+
+    #[test]
+    fn long_pauses_saturate() {
+        assert_eq!(pauses(1, 1, &[65536]), vec![u32::MAX]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Common interface for displays that draw immediately and ones that buffer.
+
+use crate::backlight::Backlight;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Update behaviour shared by all display types of this crate.
+///
+/// Generic application code should call [`flush`](Self::flush) after drawing a frame.
+/// It does nothing on displays that are drawn to directly, and sends the frame on
+/// buffered ones.
+pub trait Display {
+    /// Sends everything drawn so far to the panel.
+    fn flush(&mut self) -> Result<(), ()>;
+
+    /// Returns whether drawing only shows up on the panel after [`flush`](Self::flush).
+    fn requires_flush(&self) -> bool;
+
+    /// Returns whether updating a small part of the screen costs less than updating
+    /// all of it.
+    fn supports_partial(&self) -> bool;
+}
+
+impl<SPI, DC, RST, BL> Display for ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    fn flush(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn requires_flush(&self) -> bool {
+        false
+    }
+
+    fn supports_partial(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,996 @@
+//! In-memory framebuffer with pluggable storage.
+
+use crate::backlight::Backlight;
+use crate::display::Display;
+use crate::power::PowerState;
+use crate::ST7735;
+
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+/// Storage for the pixels of a [`FrameBuffer`], as RGB565 colors.
+///
+/// Implement this to keep the framebuffer in memory the application manages, like a
+/// DMA capable region, a static buffer or memory shared with another process.
+pub trait BufferProvider {
+    /// Returns the pixel storage.
+    fn buffer(&self) -> &[u16];
+
+    /// Returns the pixel storage for writing.
+    fn buffer_mut(&mut self) -> &mut [u16];
+}
+
+impl BufferProvider for &mut [u16] {
+    fn buffer(&self) -> &[u16] {
+        self
+    }
+
+    fn buffer_mut(&mut self) -> &mut [u16] {
+        self
+    }
+}
+
+impl<const N: usize> BufferProvider for [u16; N] {
+    fn buffer(&self) -> &[u16] {
+        self
+    }
+
+    fn buffer_mut(&mut self) -> &mut [u16] {
+        self
+    }
+}
+
+/// A frame of RGB565 pixels kept in memory and sent to the display on [`flush`](Self::flush).
+pub struct FrameBuffer<'a, B> {
+    buffer: B,
+    width: u16,
+    height: u16,
+    dead_pixels: &'a [(u16, u16)],
+    grid: Option<(u16, u16)>,
+}
+
+impl<'a, B> FrameBuffer<'a, B>
+where
+    B: BufferProvider,
+{
+    /// Creates a `width` by `height` framebuffer using the given storage.
+    ///
+    /// Returns `None` if the storage is too small for the frame.
+    pub fn new(buffer: B, width: u16, height: u16) -> Option<Self> {
+        if buffer.buffer().len() < usize::from(width) * usize::from(height) {
+            return None;
+        }
+        Some(FrameBuffer {
+            buffer,
+            width,
+            height,
+            dead_pixels: &[],
+            grid: None,
+        })
+    }
+
+    /// Returns the frame width in pixels.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Returns the frame height in pixels.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the pixels of the frame, row by row.
+    pub fn pixels(&self) -> &[u16] {
+        &self.buffer.buffer()[..usize::from(self.width) * usize::from(self.height)]
+    }
+
+    /// Returns the pixels of the frame for writing, row by row.
+    pub fn pixels_mut(&mut self) -> &mut [u16] {
+        let len = usize::from(self.width) * usize::from(self.height);
+        &mut self.buffer.buffer_mut()[..len]
+    }
+
+    /// Returns the color of the pixel at `x`, `y`, or `None` if it is outside the frame.
+    pub fn pixel(&self, x: u16, y: u16) -> Option<u16> {
+        if x < self.width && y < self.height {
+            Some(self.pixels()[self.index(x, y)])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the color of the pixel at `x`, `y`. Pixels outside the frame are ignored.
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: u16) {
+        if x < self.width && y < self.height {
+            let index = self.index(x, y);
+            self.pixels_mut()[index] = color;
+        }
+    }
+
+    /// Fills a rectangle with a single color, clipped to the frame.
+    pub fn fill_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) {
+        let ex = x.saturating_add(w).min(self.width);
+        let ey = y.saturating_add(h).min(self.height);
+        if x >= ex || y >= ey {
+            return;
+        }
+        let width = usize::from(self.width);
+        for row in self.pixels_mut()[usize::from(y) * width..usize::from(ey) * width]
+            .chunks_exact_mut(width)
+        {
+            row[usize::from(x)..usize::from(ex)].fill(color);
+        }
+    }
+
+    /// Draws the outline of a rectangle, one pixel wide and clipped to the frame.
+    pub fn outline_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        self.fill_rect(x, y, w, 1, color);
+        self.fill_rect(x, y.saturating_add(h - 1), w, 1, color);
+        self.fill_rect(x, y, 1, h, color);
+        self.fill_rect(x.saturating_add(w - 1), y, 1, h, color);
+    }
+
+    /// Fills the whole frame with a single color.
+    pub fn clear(&mut self, color: u16) {
+        self.pixels_mut().fill(color);
+    }
+
+    /// Sets the coordinates of dead or stuck pixels of the panel.
+    ///
+    /// When flushing, these pixels are sent with the average color of their working
+    /// neighbours instead of their own, so a stuck bright pixel doesn't stand out
+    /// against a dark area. The frame contents are not changed. The list is sorted by
+    /// row in place, so it can be looked up quickly while flushing.
+    pub fn set_dead_pixels(&mut self, pixels: &'a mut [(u16, u16)]) {
+        pixels.sort_unstable_by_key(|&(x, y)| (y, x));
+        self.dead_pixels = pixels;
+    }
+
+    /// Shows grid lines every `spacing` pixels in `color`, starting at the top and left
+    /// edges, for checking the alignment of a layout. `None` or a spacing of 0 turns
+    /// the grid off.
+    ///
+    /// Like dead pixel masking, the grid is applied when flushing and doesn't change
+    /// the frame contents. Call [`DoubleBuffer::invalidate`] after switching it, as
+    /// only changed parts of the frame are sent otherwise.
+    pub fn set_grid_overlay(&mut self, spacing: u16, color: Option<u16>) {
+        self.grid = color.filter(|_| spacing > 0).map(|color| (spacing, color));
+    }
+
+    /// Sends the whole frame to the display, with its top left corner at the origin.
+    pub fn flush<SPI, DC, RST, BL>(&self, display: &mut ST7735<SPI, DC, RST, BL>) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        self.flush_region(display, 0, 0, self.width, self.height)
+    }
+
+    /// Waits for the vertical blanking period signalled on the tearing effect pin `te`,
+    /// then sends the whole frame, so the panel never shows half of it.
+    ///
+    /// See [`ST7735::wait_for_vblank`] for the setup needed and when this fails.
+    pub fn flush_on_vblank<SPI, DC, RST, BL, TE, DELAY>(
+        &self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+        te: &TE,
+        delay: &mut DELAY,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+        TE: InputPin,
+        DELAY: DelayUs<u16>,
+    {
+        display.wait_for_vblank(te, delay)?;
+        self.flush(display)
+    }
+
+    /// Sends the whole frame like [`flush`](Self::flush), passing each row through
+    /// `effect` on the way.
+    ///
+    /// `effect` is called with the row index and the RGB565 colors of the row, which it
+    /// may change for scanline effects like darkening every other row or shifting the
+    /// palette towards the horizon. The frame itself is left as is. `row` is the row
+    /// storage and must hold at least a row of the frame. Dead pixels are masked before
+    /// `effect` is called, and the grid overlay is drawn after it.
+    pub fn flush_with<SPI, DC, RST, BL, F>(
+        &self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+        row: &mut [u16],
+        mut effect: F,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+        F: FnMut(u16, &mut [u16]),
+    {
+        let width = usize::from(self.width);
+        display.draw_rows(0, 0, self.width, self.height, row, |y, row| {
+            let dead = self.dead_in_row(y);
+            let start = usize::from(y) * width;
+            let colors = &self.pixels()[start..start + width];
+            for ((pixel, &color), x) in row.iter_mut().zip(colors).zip(0..) {
+                *pixel = self.mask(x, y, color, dead);
+            }
+            effect(y, row);
+            for (pixel, x) in row.iter_mut().zip(0..) {
+                *pixel = self.overlay(x, y, *pixel);
+            }
+        })
+    }
+
+    /// Sends a rectangle of the frame to the same place on the display, clipped to the
+    /// frame.
+    pub fn flush_region<SPI, DC, RST, BL>(
+        &self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        let ex = x.saturating_add(w).min(self.width);
+        let ey = y.saturating_add(h).min(self.height);
+        if x >= ex || y >= ey {
+            return Ok(());
+        }
+        let width = usize::from(self.width);
+        let rows = &self.pixels()[usize::from(y) * width..usize::from(ey) * width];
+        display.set_pixels_with_buffer(
+            x,
+            y,
+            ex - 1,
+            ey - 1,
+            rows.chunks_exact(width).zip(y..).flat_map(|(row, py)| {
+                let dead = self.dead_in_row(py);
+                row[usize::from(x)..usize::from(ex)]
+                    .iter()
+                    .zip(x..)
+                    .map(move |(&color, px)| self.output(px, py, color, dead))
+            }),
+            &mut [0; 480],
+        )
+    }
+
+    /// Returns the storage of the framebuffer.
+    pub fn release(self) -> B {
+        self.buffer
+    }
+
+    /// Returns the average color of the working pixels next to `x`, `y`.
+    fn neighbour_average(&self, x: u16, y: u16) -> Option<u16> {
+        let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+        for (nx, ny) in neighbours(x, y) {
+            if self.is_dead(nx, ny) {
+                continue;
+            }
+            if let Some(color) = self.pixel(nx, ny) {
+                r += u32::from(color >> 11);
+                g += u32::from((color >> 5) & 0x3F);
+                b += u32::from(color & 0x1F);
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+        Some(((r / count) << 11 | (g / count) << 5 | (b / count)) as u16)
+    }
+
+    /// Returns the color sent for the pixel at `x`, `y` with the given frame color,
+    /// with `dead` the dead pixels on its row.
+    fn output(&self, x: u16, y: u16, color: u16, dead: &[(u16, u16)]) -> u16 {
+        self.overlay(x, y, self.mask(x, y, color, dead))
+    }
+
+    /// Replaces the color of a dead pixel at `x`, `y` with that of its neighbours.
+    fn mask(&self, x: u16, y: u16, color: u16, dead: &[(u16, u16)]) -> u16 {
+        if dead.iter().any(|&(dx, _)| dx == x) {
+            self.neighbour_average(x, y).unwrap_or(color)
+        } else {
+            color
+        }
+    }
+
+    /// Applies the grid overlay to the color of the pixel at `x`, `y`.
+    fn overlay(&self, x: u16, y: u16, color: u16) -> u16 {
+        match self.grid {
+            Some((spacing, grid)) if x % spacing == 0 || y % spacing == 0 => grid,
+            _ => color,
+        }
+    }
+
+    /// Returns the dead pixels on row `y`.
+    fn dead_in_row(&self, y: u16) -> &'a [(u16, u16)] {
+        let start = self.dead_pixels.partition_point(|&(_, py)| py < y);
+        let end = self.dead_pixels.partition_point(|&(_, py)| py <= y);
+        &self.dead_pixels[start..end]
+    }
+
+    fn is_dead(&self, x: u16, y: u16) -> bool {
+        self.dead_in_row(y).iter().any(|&(dx, _)| dx == x)
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        usize::from(y) * usize::from(self.width) + usize::from(x)
+    }
+}
+
+/// Returns the pixels above, below and to the sides of `x`, `y`.
+fn neighbours(x: u16, y: u16) -> impl Iterator<Item = (u16, u16)> {
+    IntoIterator::into_iter([
+        (x.checked_sub(1), Some(y)),
+        (x.checked_add(1), Some(y)),
+        (Some(x), y.checked_sub(1)),
+        (Some(x), y.checked_add(1)),
+    ])
+    .filter_map(|(x, y)| Some((x?, y?)))
+}
+
+/// Returns the top, bottom, left and right edges of the rectangle `x`, `y`, `w`, `h`,
+/// which must not be empty, as rectangles of the same form.
+fn edges((x, y, w, h): (u16, u16, u16, u16)) -> [(u16, u16, u16, u16); 4] {
+    [
+        (x, y, w, 1),
+        (x, y + h - 1, w, 1),
+        (x, y, 1, h),
+        (x + w - 1, y, 1, h),
+    ]
+}
+
+/// A pair of frames for animation, sending only what changed.
+///
+/// Drawing goes to the back frame, which [`present`](Self::present) compares against
+/// the frame last sent to the display. Consecutive changed rows are sent as one
+/// rectangle spanning their changed columns, and the back frame keeps its contents so
+/// only changes need to be drawn for the next frame. Dead pixels set on the back frame
+/// are sent along when a neighbour changes, so their masked color stays current.
+///
+/// Changes are sent while the panel refreshes, so a moving object can show torn.
+/// Present with [`present_on_vblank`](Self::present_on_vblank) to avoid this.
+pub struct DoubleBuffer<'a, B> {
+    back: FrameBuffer<'a, B>,
+    front: FrameBuffer<'a, B>,
+    presented: bool,
+    damage_color: Option<u16>,
+    outlines: [Option<(u16, u16, u16, u16)>; DAMAGE_OUTLINES],
+}
+
+/// Number of rectangles a [`DoubleBuffer`] outlines per frame.
+const DAMAGE_OUTLINES: usize = 8;
+
+impl<'a, B> DoubleBuffer<'a, B>
+where
+    B: BufferProvider,
+{
+    /// Creates a `width` by `height` double buffer using the given storage for the
+    /// back and front frames.
+    ///
+    /// Returns `None` if either storage is too small for the frame.
+    pub fn new(back: B, front: B, width: u16, height: u16) -> Option<Self> {
+        Some(DoubleBuffer {
+            back: FrameBuffer::new(back, width, height)?,
+            front: FrameBuffer::new(front, width, height)?,
+            presented: false,
+            damage_color: None,
+            outlines: [None; DAMAGE_OUTLINES],
+        })
+    }
+
+    /// Returns the frame being drawn.
+    pub fn back(&self) -> &FrameBuffer<'a, B> {
+        &self.back
+    }
+
+    /// Returns the frame being drawn for writing.
+    pub fn back_mut(&mut self) -> &mut FrameBuffer<'a, B> {
+        &mut self.back
+    }
+
+    /// Makes the next [`present`](Self::present) send the whole frame, for when the
+    /// display contents were changed by other means.
+    pub fn invalidate(&mut self) {
+        self.presented = false;
+    }
+
+    /// Outlines every rectangle sent by [`present`](Self::present) on the display in
+    /// `color`, or stops doing so for `None`.
+    ///
+    /// This shows which parts of the screen each frame updates, for up to 8 rectangles
+    /// per frame. The outlines are only drawn on the display, not in the back frame.
+    /// The next call to `present` restores the pixels under outlines that aren't drawn
+    /// again by sending just those edges, so outlines don't add to the damage.
+    pub fn set_damage_outline(&mut self, color: Option<u16>) {
+        self.damage_color = color;
+    }
+
+    /// Sends the parts of the back frame that differ from the last presented frame to
+    /// the display, with its top left corner at the origin.
+    ///
+    /// The first call after creation or [`invalidate`](Self::invalidate) sends the
+    /// whole frame.
+    pub fn present<SPI, DC, RST, BL>(
+        &mut self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        let width = usize::from(self.back.width);
+        let mut outlines = [None; DAMAGE_OUTLINES];
+        let mut outlined = 0;
+        // Top row and column span of the changed rows not sent yet
+        let mut pending: Option<(u16, usize, usize)> = None;
+        for y in 0..=self.back.height {
+            let span = if y == self.back.height {
+                None
+            } else if !self.presented {
+                Some((0, width))
+            } else {
+                let start = usize::from(y) * width;
+                let back = &self.back.pixels()[start..start + width];
+                let front = &self.front.pixels()[start..start + width];
+                let mut span = back
+                    .iter()
+                    .zip(front)
+                    .position(|(b, f)| b != f)
+                    .map(|left| {
+                        let right = back.iter().zip(front).rposition(|(b, f)| b != f);
+                        (left, right.unwrap_or(left) + 1)
+                    });
+                for &(x, _) in self.back.dead_in_row(y) {
+                    if neighbours(x, y).any(|(nx, ny)| self.changed(nx, ny)) {
+                        let (left, right) = span.unwrap_or((usize::from(x), usize::from(x) + 1));
+                        span = Some((left.min(usize::from(x)), right.max(usize::from(x) + 1)));
+                    }
+                }
+                span
+            };
+            pending = match (pending, span) {
+                (Some((top, left, right)), Some((l, r))) => Some((top, left.min(l), right.max(r))),
+                (None, Some((l, r))) => Some((y, l, r)),
+                (Some((top, left, right)), None) => {
+                    let rect = (left as u16, top, (right - left) as u16, y - top);
+                    self.back
+                        .flush_region(display, rect.0, rect.1, rect.2, rect.3)?;
+                    let rows = usize::from(top) * width..usize::from(y) * width;
+                    self.front.pixels_mut()[rows.clone()]
+                        .copy_from_slice(&self.back.pixels()[rows]);
+                    if let Some(slot) = outlines.get_mut(outlined) {
+                        *slot = Some(rect);
+                        outlined += 1;
+                    }
+                    None
+                }
+                (None, None) => None,
+            };
+        }
+        self.presented = true;
+
+        // Restore the pixels under the last outlines before drawing the new ones
+        let last = core::mem::replace(&mut self.outlines, outlines);
+        for &rect in last.iter().flatten() {
+            if !outlines.contains(&Some(rect)) {
+                for (x, y, w, h) in edges(rect) {
+                    self.back.flush_region(display, x, y, w, h)?;
+                }
+            }
+        }
+        match self.damage_color {
+            Some(color) => {
+                for &rect in outlines.iter().flatten() {
+                    for (x, y, w, h) in edges(rect) {
+                        display.fill_rect(x, y, w, h, color)?;
+                    }
+                }
+            }
+            None => self.outlines = [None; DAMAGE_OUTLINES],
+        }
+        Ok(())
+    }
+
+    /// Waits for the vertical blanking period signalled on the tearing effect pin `te`,
+    /// then sends the changes like [`present`](Self::present).
+    ///
+    /// See [`ST7735::wait_for_vblank`] for the setup needed and when this fails.
+    pub fn present_on_vblank<SPI, DC, RST, BL, TE, DELAY>(
+        &mut self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+        te: &TE,
+        delay: &mut DELAY,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+        TE: InputPin,
+        DELAY: DelayUs<u16>,
+    {
+        display.wait_for_vblank(te, delay)?;
+        self.present(display)
+    }
+
+    /// Returns whether the pixel at `x`, `y` differs from the last presented frame.
+    fn changed(&self, x: u16, y: u16) -> bool {
+        self.back.pixel(x, y) != self.front.pixel(x, y)
+    }
+
+    /// Returns the storage of the back and front frames.
+    pub fn release(self) -> (B, B) {
+        (self.back.release(), self.front.release())
+    }
+}
+
+/// A display together with a framebuffer that is drawn to and sent on
+/// [`flush`](Display::flush).
+///
+/// This gives a buffered display the same [`Display`] interface as an [`ST7735`] that
+/// is drawn to directly. While the display sleeps or its panel is off, flushing only
+/// keeps the frame in memory, and the frame is sent once when the panel is turned back
+/// on through [`wake`](Self::wake) or [`set_display_on`](Self::set_display_on).
+pub struct BufferedDisplay<'a, SPI, DC, RST, BL, B>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    display: ST7735<SPI, DC, RST, BL>,
+    frame: FrameBuffer<'a, B>,
+}
+
+impl<'a, SPI, DC, RST, BL, B> BufferedDisplay<'a, SPI, DC, RST, BL, B>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+    B: BufferProvider,
+{
+    /// Pairs `display` with `frame`, which is sent with its top left corner at the
+    /// origin.
+    pub fn new(display: ST7735<SPI, DC, RST, BL>, frame: FrameBuffer<'a, B>) -> Self {
+        BufferedDisplay { display, frame }
+    }
+
+    /// Returns the display, for settings and direct drawing.
+    pub fn display_mut(&mut self) -> &mut ST7735<SPI, DC, RST, BL> {
+        &mut self.display
+    }
+
+    /// Returns the framebuffer.
+    pub fn frame(&self) -> &FrameBuffer<'a, B> {
+        &self.frame
+    }
+
+    /// Returns the framebuffer for drawing.
+    pub fn frame_mut(&mut self) -> &mut FrameBuffer<'a, B> {
+        &mut self.frame
+    }
+
+    /// Wakes the display up from sleep mode and sends the frame if the panel is on.
+    pub fn wake<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.display.wake(delay)?;
+        self.flush()
+    }
+
+    /// Turns the panel output on or off, sending the frame when it is turned on while
+    /// the display is awake.
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), ()> {
+        self.display.set_display_on(on)?;
+        self.flush()
+    }
+
+    /// Returns the display and the framebuffer.
+    pub fn release(self) -> (ST7735<SPI, DC, RST, BL>, FrameBuffer<'a, B>) {
+        (self.display, self.frame)
+    }
+}
+
+impl<SPI, DC, RST, BL, B> Display for BufferedDisplay<'_, SPI, DC, RST, BL, B>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+    B: BufferProvider,
+{
+    fn flush(&mut self) -> Result<(), ()> {
+        match self.display.power_state() {
+            PowerState::Sleep | PowerState::Off => Ok(()),
+            PowerState::On | PowerState::Idle => self.frame.flush(&mut self.display),
+        }
+    }
+
+    fn requires_flush(&self) -> bool {
+        true
+    }
+
+    fn supports_partial(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::{
+        raw::{RawData, RawU16},
+        Rgb565,
+    },
+    prelude::*,
+    primitives::Rectangle,
+};
+
+#[cfg(feature = "graphics")]
+impl<B> DrawTarget for FrameBuffer<'_, B>
+where
+    B: BufferProvider,
+{
+    type Error = core::convert::Infallible;
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            // Only draw pixels inside the frame, checked before the coordinates are narrowed
+            if coord.x >= 0
+                && coord.y >= 0
+                && coord.x < i32::from(self.width)
+                && coord.y < i32::from(self.height)
+            {
+                self.set_pixel(
+                    coord.x as u16,
+                    coord.y as u16,
+                    RawU16::from(color).into_inner(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+        self.fill_rect(
+            area.top_left.x as u16,
+            area.top_left.y as u16,
+            area.size.width as u16,
+            area.size.height as u16,
+            RawU16::from(color).into_inner(),
+        );
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        FrameBuffer::clear(self, RawU16::from(color).into_inner());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<B> OriginDimensions for FrameBuffer<'_, B>
+where
+    B: BufferProvider,
+{
+    fn size(&self) -> Size {
+        Size::new(u32::from(self.width), u32::from(self.height))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SPI, DC, RST, BL, B> DrawTarget for BufferedDisplay<'_, SPI, DC, RST, BL, B>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+    B: BufferProvider,
+{
+    type Error = core::convert::Infallible;
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.frame.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.frame.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        DrawTarget::clear(&mut self.frame, color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SPI, DC, RST, BL, B> OriginDimensions for BufferedDisplay<'_, SPI, DC, RST, BL, B>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+    B: BufferProvider,
+{
+    fn size(&self) -> Size {
+        self.frame.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::instruction::Instruction;
+    use crate::mock::{MockDc, MockDelay, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(recorder.spi(), recorder.dc(), MockPin, true, false, 8, 8)
+    }
+
+    /// Returns the window of every memory write as `sx`, `sy`, `ex`, `ey`.
+    fn writes(recorder: &Recorder) -> Vec<(u16, u16, u16, u16)> {
+        let (mut columns, mut rows) = ((0, 0), (0, 0));
+        let mut writes = Vec::new();
+        for transaction in recorder.transactions() {
+            match transaction {
+                Transaction::Command(Instruction::CASET, data) => {
+                    columns = (
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                    )
+                }
+                Transaction::Command(Instruction::RASET, data) => {
+                    rows = (
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                    )
+                }
+                Transaction::Command(Instruction::RAMWR, _) => {
+                    writes.push((columns.0, rows.0, columns.1, rows.1))
+                }
+                _ => {}
+            }
+        }
+        writes
+    }
+
+    #[test]
+    fn present_sends_only_changed_spans() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut frames = DoubleBuffer::new([0; 64], [0; 64], 8, 8).unwrap();
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder), vec![(0, 0, 7, 7)]);
+
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 1);
+
+        // Consecutive rows merge into one rectangle, a gap starts another
+        frames.back_mut().set_pixel(2, 1, 1);
+        frames.back_mut().set_pixel(5, 2, 1);
+        frames.back_mut().set_pixel(3, 2, 1);
+        frames.back_mut().set_pixel(6, 5, 1);
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[1..], [(2, 1, 5, 2), (6, 5, 6, 5)]);
+
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 3);
+    }
+
+    #[test]
+    fn invalidate_resends_the_whole_frame() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut frames = DoubleBuffer::new([0; 64], [0; 64], 8, 8).unwrap();
+        frames.present(&mut display).unwrap();
+        frames.back_mut().set_pixel(7, 7, 1);
+        frames.invalidate();
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder), vec![(0, 0, 7, 7), (0, 0, 7, 7)]);
+    }
+
+    /// Returns the RGB565 colors sent as pixel data.
+    fn colors(recorder: &Recorder) -> Vec<u16> {
+        recorder
+            .transactions()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Data(data) => Some(data),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<u8>>()
+            .chunks_exact(2)
+            .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn dead_pixels_are_sent_as_their_neighbours_average() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut dead = [(3, 3), (1, 1), (0, 3)];
+        let mut frame = FrameBuffer::new([0; 16], 4, 4).unwrap();
+        frame.set_dead_pixels(&mut dead);
+        frame.set_pixel(1, 1, 0xFFFF);
+        frame.set_pixel(0, 1, 0xF800);
+        frame.set_pixel(2, 1, 0xF800);
+        frame.set_pixel(1, 0, 0x001F);
+        frame.set_pixel(1, 2, 0x001F);
+        frame.set_pixel(3, 3, 0xFFFF);
+        frame.flush(&mut display).unwrap();
+        let colors = colors(&recorder);
+        assert_eq!(colors[5], 0x780F);
+        assert_eq!(colors[15], 0);
+        assert_eq!(frame.pixel(1, 1), Some(0xFFFF));
+        assert_eq!(dead, [(1, 1), (0, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn present_resends_dead_pixels_next_to_changes() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut dead = [(3, 2)];
+        let mut frames = DoubleBuffer::new([0; 64], [0; 64], 8, 8).unwrap();
+        frames.back_mut().set_dead_pixels(&mut dead);
+        frames.present(&mut display).unwrap();
+
+        frames.back_mut().set_pixel(2, 2, 1);
+        frames.back_mut().set_pixel(3, 1, 1);
+        frames.back_mut().set_pixel(6, 6, 1);
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[1..], [(2, 1, 3, 2), (6, 6, 6, 6)]);
+    }
+
+    #[test]
+    fn grid_overlay_is_applied_when_flushing() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut frame = FrameBuffer::new([0; 16], 4, 4).unwrap();
+        frame.set_grid_overlay(2, Some(0xFFFF));
+        frame.flush(&mut display).unwrap();
+        let expected = [
+            [0xFFFF; 4],
+            [0xFFFF, 0, 0xFFFF, 0],
+            [0xFFFF; 4],
+            [0xFFFF, 0, 0xFFFF, 0],
+        ];
+        assert_eq!(colors(&recorder), expected.concat());
+        assert!(frame.pixels().iter().all(|&pixel| pixel == 0));
+
+        recorder.clear();
+        frame.set_grid_overlay(0, Some(0xFFFF));
+        frame.flush(&mut display).unwrap();
+        assert_eq!(colors(&recorder), [0; 16]);
+    }
+
+    #[test]
+    fn damage_outlines_are_replaced_every_frame() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut frames = DoubleBuffer::new([0; 64], [0; 64], 8, 8).unwrap();
+        frames.set_damage_outline(Some(0xF800));
+        frames.present(&mut display).unwrap();
+        let full = [(0, 0, 7, 0), (0, 7, 7, 7), (0, 0, 0, 7), (7, 0, 7, 7)];
+        assert_eq!(writes(&recorder)[1..], full);
+
+        // Only the edges of the last outline are sent to restore it
+        frames.back_mut().set_pixel(2, 2, 1);
+        frames.back_mut().set_pixel(3, 3, 1);
+        frames.present(&mut display).unwrap();
+        let small = [(2, 2, 3, 2), (2, 3, 3, 3), (2, 2, 2, 3), (3, 2, 3, 3)];
+        assert_eq!(writes(&recorder)[5], (2, 2, 3, 3));
+        assert_eq!(writes(&recorder)[6..10], full);
+        assert_eq!(writes(&recorder)[10..], small);
+
+        // Outlines disappear once nothing changes
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[14..], small);
+        frames.present(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 18);
+    }
+
+    #[test]
+    fn flush_with_passes_rows_through_the_effect() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut frame = FrameBuffer::new([0xFFFF; 8], 4, 2).unwrap();
+        frame.set_pixel(3, 0, 0x1234);
+        let mut row = [0; 8];
+        frame
+            .flush_with(&mut display, &mut row, |y, row| {
+                if y % 2 == 1 {
+                    row.fill(0);
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            colors(&recorder),
+            [0xFFFF, 0xFFFF, 0xFFFF, 0x1234, 0, 0, 0, 0]
+        );
+        assert_eq!(frame.pixel(0, 1), Some(0xFFFF));
+        assert_eq!(
+            frame.flush_with(&mut display, &mut [0; 3], |_, _| {}),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn buffered_display_holds_frames_while_asleep() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        display.init(&mut MockDelay).unwrap();
+        let frame = FrameBuffer::new([0; 64], 8, 8).unwrap();
+        let mut buffered = BufferedDisplay::new(display, frame);
+        buffered.display_mut().sleep(&mut MockDelay).unwrap();
+        recorder.clear();
+
+        buffered.frame_mut().set_pixel(0, 0, 0xFFFF);
+        buffered.flush().unwrap();
+        assert!(recorder.transactions().is_empty());
+
+        buffered.wake(&mut MockDelay).unwrap();
+        assert_eq!(writes(&recorder), [(0, 0, 7, 7)]);
+
+        buffered.set_display_on(false).unwrap();
+        buffered.flush().unwrap();
+        assert_eq!(writes(&recorder).len(), 1);
+        buffered.set_display_on(true).unwrap();
+        assert_eq!(writes(&recorder).len(), 2);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn draw_iter_ignores_pixels_outside_the_frame() {
+        let mut frame = FrameBuffer::new([0; 64], 8, 8).unwrap();
+        frame
+            .draw_iter([
+                Pixel(Point::new(-1, 0), Rgb565::WHITE),
+                Pixel(Point::new(8, 0), Rgb565::WHITE),
+                Pixel(Point::new(0, 8), Rgb565::WHITE),
+                Pixel(Point::new(65541, 0), Rgb565::WHITE),
+                Pixel(Point::new(0, 65541), Rgb565::WHITE),
+            ])
+            .unwrap();
+        assert!(frame.pixels().iter().all(|&pixel| pixel == 0));
+
+        frame
+            .draw_iter([Pixel(Point::new(7, 7), Rgb565::WHITE)])
+            .unwrap();
+        assert_eq!(frame.pixel(7, 7), Some(0xFFFF));
+    }
+}
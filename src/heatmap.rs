@@ -0,0 +1,240 @@
+//! Drawing grids of measurements, like thermal camera frames, as colored heatmaps.
+
+use crate::backlight::Backlight;
+use crate::palette::rgb;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Mapping of levels from low to high to colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Black to white.
+    Grayscale,
+    /// Dark blue through green to yellow, evenly increasing in lightness and readable
+    /// with color blindness.
+    Viridis,
+    /// Dark blue through cyan, green, yellow and red, with more visible detail than
+    /// Viridis but uneven lightness.
+    Turbo,
+}
+
+impl Colormap {
+    /// Returns the RGB565 color of `level`, from 0 for the lowest to 255 for the
+    /// highest.
+    pub fn color(self, level: u8) -> u16 {
+        let table = match self {
+            Colormap::Grayscale => return rgb(level, level, level),
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Turbo => &TURBO,
+        };
+        // Spread the levels over the 16 steps between table entries
+        let position = u32::from(level) * 16;
+        let (index, fraction) = ((position / 255) as usize, position % 255);
+        let (low, high) = (table[index], table[(index + 1).min(16)]);
+        let mix = |channel: usize| {
+            let (low, high) = (u32::from(low[channel]), u32::from(high[channel]));
+            ((low * (255 - fraction) + high * fraction) / 255) as u8
+        };
+        rgb(mix(0), mix(1), mix(2))
+    }
+}
+
+/// A measurement that can be drawn in a heatmap.
+pub trait HeatmapSample: Copy {
+    /// Returns where the sample lies between `min` and `max`, from 0 to 255.
+    fn level(self, min: Self, max: Self) -> u8;
+}
+
+impl HeatmapSample for f32 {
+    fn level(self, min: f32, max: f32) -> u8 {
+        if max <= min || self.is_nan() {
+            return 0;
+        }
+        ((self - min) / (max - min) * 255.0).clamp(0.0, 255.0) as u8
+    }
+}
+
+impl HeatmapSample for u16 {
+    fn level(self, min: u16, max: u16) -> u8 {
+        if max <= min {
+            return 0;
+        }
+        let value = u32::from(self.clamp(min, max) - min);
+        (value * 255 / u32::from(max - min)) as u8
+    }
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Draws `grid`, a row by row array of samples that is `grid_width` samples wide,
+    /// scaled to fill the `w` by `h` pixel area at `x`, `y`.
+    ///
+    /// Samples are colored by where they lie between `min` and `max` in `colormap`,
+    /// values outside of the range get the color of the nearest end. Returns an error
+    /// if the grid doesn't hold a whole number of rows or the area extends past the
+    /// largest coordinate.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_heatmap<T>(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        grid: &[T],
+        grid_width: usize,
+        min: T,
+        max: T,
+        colormap: Colormap,
+    ) -> Result<(), ()>
+    where
+        T: HeatmapSample,
+    {
+        if grid.is_empty() || grid_width == 0 || grid.len() % grid_width != 0 {
+            return Err(());
+        }
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        let ex = x.checked_add(w - 1).ok_or(())?;
+        let ey = y.checked_add(h - 1).ok_or(())?;
+        let grid_height = grid.len() / grid_width;
+        let (w, h) = (usize::from(w), usize::from(h));
+        self.set_pixels_buffered(
+            x,
+            y,
+            ex,
+            ey,
+            (0..h)
+                .flat_map(|py| (0..w).map(move |px| (px, py)))
+                .map(|(px, py)| {
+                    let sample = grid[py * grid_height / h * grid_width + px * grid_width / w];
+                    colormap.color(sample.level(min, max))
+                }),
+        )
+    }
+}
+
+/// Colors at every 16 levels, sampled from polynomial fits of the published colormaps.
+static VIRIDIS: [[u8; 3]; 17] = [
+    [71, 1, 85],
+    [72, 24, 106],
+    [71, 45, 123],
+    [67, 64, 134],
+    [61, 82, 140],
+    [52, 99, 142],
+    [43, 114, 142],
+    [35, 129, 141],
+    [31, 144, 139],
+    [33, 159, 135],
+    [42, 174, 128],
+    [61, 188, 116],
+    [90, 200, 97],
+    [128, 211, 73],
+    [172, 220, 48],
+    [216, 226, 29],
+    [252, 231, 33],
+];
+
+static TURBO: [[u8; 3]; 17] = [
+    [48, 18, 59],
+    [73, 62, 175],
+    [68, 106, 238],
+    [50, 149, 247],
+    [38, 189, 225],
+    [41, 221, 187],
+    [64, 243, 146],
+    [102, 253, 109],
+    [150, 250, 80],
+    [198, 235, 59],
+    [238, 208, 45],
+    [255, 171, 36],
+    [255, 128, 29],
+    [238, 84, 21],
+    [201, 45, 12],
+    [161, 18, 2],
+    [122, 4, 3],
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec::Vec;
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(recorder.spi(), recorder.dc(), MockPin, true, false, 8, 8)
+    }
+
+    /// Returns the RGB565 colors sent as pixel data.
+    fn colors(recorder: &Recorder) -> Vec<u16> {
+        recorder
+            .transactions()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Data(data) => Some(data),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<u8>>()
+            .chunks_exact(2)
+            .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn colormap_ends_match_the_tables() {
+        for (colormap, table) in [(Colormap::Viridis, &VIRIDIS), (Colormap::Turbo, &TURBO)] {
+            let [r, g, b] = table[0];
+            assert_eq!(colormap.color(0), rgb(r, g, b));
+            let [r, g, b] = table[16];
+            assert_eq!(colormap.color(255), rgb(r, g, b));
+        }
+        assert_eq!(Colormap::Grayscale.color(0), 0x0000);
+        assert_eq!(Colormap::Grayscale.color(255), 0xFFFF);
+    }
+
+    #[test]
+    fn colormap_interpolates_between_entries() {
+        // Level 8 lies halfway between the first two entries
+        assert_eq!(Colormap::Viridis.color(8), rgb(71, 12, 95));
+    }
+
+    #[test]
+    fn samples_are_scaled_to_the_area() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let grid: [u16; 4] = [0, 255, 255, 0];
+        display
+            .draw_heatmap(0, 0, 4, 2, &grid, 2, 0, 255, Colormap::Grayscale)
+            .unwrap();
+        assert_eq!(
+            colors(&recorder),
+            [0, 0, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0, 0]
+        );
+    }
+
+    #[test]
+    fn invalid_grids_and_areas_are_rejected() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let grid: [u16; 4] = [0; 4];
+        let draw = |display: &mut ST7735<_, _, _>, x, y, grid: &[u16], grid_width| {
+            display.draw_heatmap(x, y, 2, 2, grid, grid_width, 0, 1, Colormap::Turbo)
+        };
+        assert_eq!(draw(&mut display, 0, 0, &grid, 3), Err(()));
+        assert_eq!(draw(&mut display, 0, 0, &grid[..0], 2), Err(()));
+        assert_eq!(draw(&mut display, u16::MAX, 0, &grid, 2), Err(()));
+        assert_eq!(draw(&mut display, 0, u16::MAX, &grid, 2), Err(()));
+        assert_eq!(draw(&mut display, 0, 0, &grid, 2), Ok(()));
+    }
+}
@@ -0,0 +1,315 @@
+//! Loading of BMP and raw RGB565 images.
+
+use crate::backlight::Backlight;
+use crate::pixels::PixelFormat;
+use crate::ST7735;
+
+use core::convert::TryFrom;
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Pixel layout of a BMP image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BmpFormat {
+    Rgb555,
+    Rgb565,
+    Bgr888,
+}
+
+/// An uncompressed 16 or 24 bit BMP image.
+#[derive(Debug, Clone, Copy)]
+pub struct Bmp<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    stride: usize,
+    top_down: bool,
+    format: BmpFormat,
+}
+
+impl<'a> Bmp<'a> {
+    /// Parses a BMP file, returning `None` if it is malformed or uses an unsupported
+    /// format.
+    ///
+    /// Supported are 24 bit images and 16 bit images in RGB555 or RGB565.
+    pub fn parse(file: &'a [u8]) -> Option<Self> {
+        if file.get(0..2)? != b"BM" {
+            return None;
+        }
+        let offset = read_u32(file, 10)? as usize;
+        let header_size = read_u32(file, 14)?;
+        let width = read_u32(file, 18)? as i32;
+        let height = read_u32(file, 22)? as i32;
+        let bpp = read_u16(file, 28)?;
+        let compression = read_u32(file, 30)?;
+
+        let format = match (bpp, compression) {
+            (24, 0) => BmpFormat::Bgr888,
+            (16, 0) => BmpFormat::Rgb555,
+            (16, 3) => {
+                // Color masks follow the 40 byte info header
+                let masks = 14 + 40;
+                let red = read_u32(file, masks)?;
+                let green = read_u32(file, masks + 4)?;
+                let blue = read_u32(file, masks + 8)?;
+                match (red, green, blue) {
+                    (0xF800, 0x07E0, 0x001F) => BmpFormat::Rgb565,
+                    (0x7C00, 0x03E0, 0x001F) => BmpFormat::Rgb555,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        if header_size < 40 || width <= 0 || height == 0 {
+            return None;
+        }
+
+        let width = u16::try_from(width).ok()?;
+        let top_down = height < 0;
+        let height = u16::try_from(height.unsigned_abs()).ok()?;
+        let stride = (usize::from(width) * usize::from(bpp) / 8 + 3) / 4 * 4;
+        let end = stride
+            .checked_mul(usize::from(height))
+            .and_then(|len| len.checked_add(offset))?;
+        let data = file.get(offset..end)?;
+        Some(Bmp {
+            data,
+            width,
+            height,
+            stride,
+            top_down,
+            format,
+        })
+    }
+
+    /// Returns the image width in pixels.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Returns the image height in pixels.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the RGB565 color of the pixel at `x`, `y`, or `None` outside of the
+    /// image.
+    pub fn pixel(&self, x: u16, y: u16) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let row = if self.top_down {
+            y
+        } else {
+            self.height - 1 - y
+        };
+        let start = usize::from(row) * self.stride;
+        let color = match self.format {
+            BmpFormat::Bgr888 => {
+                let index = start + usize::from(x) * 3;
+                let (b, g, r) = (self.data[index], self.data[index + 1], self.data[index + 2]);
+                (u16::from(r >> 3) << 11) | (u16::from(g >> 2) << 5) | u16::from(b >> 3)
+            }
+            BmpFormat::Rgb565 => {
+                let index = start + usize::from(x) * 2;
+                u16::from_le_bytes([self.data[index], self.data[index + 1]])
+            }
+            BmpFormat::Rgb555 => {
+                let index = start + usize::from(x) * 2;
+                let color = u16::from_le_bytes([self.data[index], self.data[index + 1]]);
+                // Widen green to 6 bits by repeating its top bit
+                ((color & 0x7FE0) << 1) | ((color & 0x0200) >> 4) | (color & 0x001F)
+            }
+        };
+        Some(color)
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Draws a BMP file with its top left corner at `x`, `y`, clipped to the display.
+    ///
+    /// Returns an error if the file can't be parsed, see [`Bmp::parse`].
+    pub fn draw_bmp(&mut self, x: i32, y: i32, file: &[u8]) -> Result<(), ()> {
+        let bmp = Bmp::parse(file).ok_or(())?;
+        self.draw_image_with(x, y, bmp.width, bmp.height, |px, py| {
+            bmp.pixel(px, py).unwrap_or(0)
+        })
+    }
+
+    /// Draws a raw dump of big-endian RGB565 pixels that is `width` pixels wide, with
+    /// its top left corner at `x`, `y`, clipped to the display.
+    ///
+    /// Returns an error if the data doesn't hold a whole number of rows.
+    ///
+    /// Images that are fully on screen are sent with [`blit`](Self::blit) when the
+    /// display uses RGB565.
+    pub fn draw_raw_image(&mut self, x: i32, y: i32, width: u16, data: &[u8]) -> Result<(), ()> {
+        let stride = usize::from(width) * 2;
+        if stride == 0 || data.len() % stride != 0 {
+            return Err(());
+        }
+        let height = u16::try_from(data.len() / stride).map_err(|_| ())?;
+        if self.pixel_format == PixelFormat::Rgb565
+            && x >= 0
+            && y >= 0
+            && x <= self.width as i32 - i32::from(width)
+            && y <= self.height as i32 - i32::from(height)
+        {
+            return self.blit(x as u16, y as u16, width, height, data);
+        }
+        self.draw_image_with(x, y, width, height, |px, py| {
+            let index = usize::from(py) * stride + usize::from(px) * 2;
+            u16::from_be_bytes([data[index], data[index + 1]])
+        })
+    }
+
+    /// Draws the visible part of an image whose pixels are looked up by `pixel`.
+    fn draw_image_with<F>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u16,
+        height: u16,
+        pixel: F,
+    ) -> Result<(), ()>
+    where
+        F: Fn(u16, u16) -> u16,
+    {
+        let left = (-x).clamp(0, i32::from(width));
+        let top = (-y).clamp(0, i32::from(height));
+        let right = (self.width as i32 - x).clamp(left, i32::from(width));
+        let bottom = (self.height as i32 - y).clamp(top, i32::from(height));
+        if left == right || top == bottom {
+            return Ok(());
+        }
+
+        self.set_pixels_buffered(
+            (x + left) as u16,
+            (y + top) as u16,
+            (x + right - 1) as u16,
+            (y + bottom - 1) as u16,
+            (top..bottom)
+                .flat_map(|py| (left..right).map(move |px| (px as u16, py as u16)))
+                .map(|(px, py)| pixel(px, py)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::instruction::Instruction;
+    use crate::mock::{MockPin, Recorder, Transaction};
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Returns a 24 bit BMP file header for a `width` by `height` image.
+    fn header(width: i32, height: i32) -> Vec<u8> {
+        let mut file = vec![0; 54];
+        file[0..2].copy_from_slice(b"BM");
+        file[10..14].copy_from_slice(&54u32.to_le_bytes());
+        file[14..18].copy_from_slice(&40u32.to_le_bytes());
+        file[18..22].copy_from_slice(&width.to_le_bytes());
+        file[22..26].copy_from_slice(&height.to_le_bytes());
+        file[26..28].copy_from_slice(&1u16.to_le_bytes());
+        file[28..30].copy_from_slice(&24u16.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn parse_reads_bottom_up_rows_with_padding() {
+        let mut file = header(2, 2);
+        // Rows of 6 bytes padded to 8, bottom row first, pixels in BGR order
+        file.extend_from_slice(&[0xFF, 0, 0, 0, 0xFF, 0, 0, 0]);
+        file.extend_from_slice(&[0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0]);
+        let bmp = Bmp::parse(&file).unwrap();
+        assert_eq!((bmp.width(), bmp.height()), (2, 2));
+        assert_eq!(bmp.pixel(0, 0), Some(0xF800));
+        assert_eq!(bmp.pixel(1, 0), Some(0xFFFF));
+        assert_eq!(bmp.pixel(0, 1), Some(0x001F));
+        assert_eq!(bmp.pixel(1, 1), Some(0x07E0));
+        assert_eq!(bmp.pixel(2, 0), None);
+        assert_eq!(bmp.pixel(0, 2), None);
+    }
+
+    #[test]
+    fn parse_reads_top_down_rows() {
+        let mut file = header(1, -2);
+        file.extend_from_slice(&[0, 0, 0xFF, 0]);
+        file.extend_from_slice(&[0xFF, 0, 0, 0]);
+        let bmp = Bmp::parse(&file).unwrap();
+        assert_eq!(bmp.pixel(0, 0), Some(0xF800));
+        assert_eq!(bmp.pixel(0, 1), Some(0x001F));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_files() {
+        assert!(Bmp::parse(b"BM").is_none());
+        assert!(Bmp::parse(&header(0, 1)).is_none());
+        assert!(Bmp::parse(&header(1, 0)).is_none());
+        // Pixel data shorter than the header says
+        let mut file = header(2, 2);
+        file.extend_from_slice(&[0; 15]);
+        assert!(Bmp::parse(&file).is_none());
+        assert!(Bmp::parse(&header(65535, 65535)).is_none());
+        // Pixel data offset near the end of the address space
+        let mut file = header(1, 1);
+        file[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Bmp::parse(&file).is_none());
+    }
+
+    #[test]
+    fn draw_raw_image_blits_visible_images() {
+        let recorder = Recorder::new();
+        let mut display = ST7735::new(
+            recorder.spi(),
+            recorder.dc(),
+            MockPin,
+            true,
+            false,
+            128,
+            160,
+        );
+        let data = [0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F, 0xFF, 0xFF];
+        display.draw_raw_image(1, 2, 2, &data).unwrap();
+        assert_eq!(
+            recorder.transactions(),
+            vec![
+                Transaction::Command(Instruction::CASET, vec![0, 1, 0, 2]),
+                Transaction::Command(Instruction::RASET, vec![0, 2, 0, 3]),
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(data.to_vec()),
+            ]
+        );
+
+        // Partly visible images are clipped
+        recorder.clear();
+        display.draw_raw_image(-1, 0, 2, &data).unwrap();
+        assert_eq!(
+            recorder.transactions()[2..],
+            [
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(vec![0x07, 0xE0, 0xFF, 0xFF]),
+            ]
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use core::convert::TryFrom;
+
 /// ST7735 instructions.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     NOP = 0x00,
     SWRESET = 0x01,
@@ -18,8 +20,14 @@ pub enum Instruction {
     RAMWR = 0x2C,
     RAMRD = 0x2E,
     PTLAR = 0x30,
+    VSCRDEF = 0x33,
+    TEOFF = 0x34,
+    TEON = 0x35,
     COLMOD = 0x3A,
     MADCTL = 0x36,
+    VSCSAD = 0x37,
+    IDMOFF = 0x38,
+    IDMON = 0x39,
     FRMCTR1 = 0xB1,
     FRMCTR2 = 0xB2,
     FRMCTR3 = 0xB3,
@@ -39,3 +47,57 @@ pub enum Instruction {
     GMCTRP1 = 0xE0,
     GMCTRN1 = 0xE1,
 }
+
+impl TryFrom<u8> for Instruction {
+    type Error = ();
+
+    /// Decodes a command byte, failing for bytes that aren't a known instruction.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x00 => Instruction::NOP,
+            0x01 => Instruction::SWRESET,
+            0x04 => Instruction::RDDID,
+            0x09 => Instruction::RDDST,
+            0x10 => Instruction::SLPIN,
+            0x11 => Instruction::SLPOUT,
+            0x12 => Instruction::PTLON,
+            0x13 => Instruction::NORON,
+            0x20 => Instruction::INVOFF,
+            0x21 => Instruction::INVON,
+            0x28 => Instruction::DISPOFF,
+            0x29 => Instruction::DISPON,
+            0x2A => Instruction::CASET,
+            0x2B => Instruction::RASET,
+            0x2C => Instruction::RAMWR,
+            0x2E => Instruction::RAMRD,
+            0x30 => Instruction::PTLAR,
+            0x33 => Instruction::VSCRDEF,
+            0x34 => Instruction::TEOFF,
+            0x35 => Instruction::TEON,
+            0x3A => Instruction::COLMOD,
+            0x36 => Instruction::MADCTL,
+            0x37 => Instruction::VSCSAD,
+            0x38 => Instruction::IDMOFF,
+            0x39 => Instruction::IDMON,
+            0xB1 => Instruction::FRMCTR1,
+            0xB2 => Instruction::FRMCTR2,
+            0xB3 => Instruction::FRMCTR3,
+            0xB4 => Instruction::INVCTR,
+            0xB6 => Instruction::DISSET5,
+            0xC0 => Instruction::PWCTR1,
+            0xC1 => Instruction::PWCTR2,
+            0xC2 => Instruction::PWCTR3,
+            0xC3 => Instruction::PWCTR4,
+            0xC4 => Instruction::PWCTR5,
+            0xC5 => Instruction::VMCTR1,
+            0xDA => Instruction::RDID1,
+            0xDB => Instruction::RDID2,
+            0xDC => Instruction::RDID3,
+            0xDD => Instruction::RDID4,
+            0xFC => Instruction::PWCTR6,
+            0xE0 => Instruction::GMCTRP1,
+            0xE1 => Instruction::GMCTRN1,
+            _ => return Err(()),
+        })
+    }
+}
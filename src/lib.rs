@@ -1,21 +1,57 @@
 #![no_std]
+// The driver has reported failures as `Result<_, ()>` since its first release
+#![allow(clippy::result_unit_err)]
 
 //! This crate provides a ST7735 driver to connect to TFT displays.
 
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod backlight;
+pub mod blend;
+pub mod bus;
+pub mod display;
+pub mod framebuffer;
+pub mod heatmap;
+#[cfg(feature = "image")]
+pub mod image;
 pub mod instruction;
+pub mod meter;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod palette;
+pub mod pixels;
+pub mod power;
+pub mod read;
+#[cfg(feature = "graphics")]
+pub mod region;
+pub mod rotation;
+pub mod shapes;
+pub mod soft_spi;
+pub mod sprite;
+pub mod stream;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod tilemap;
 
+use crate::backlight::{Backlight, NoBacklight, PinBacklight, PwmBacklight};
 use crate::instruction::Instruction;
+use crate::pixels::{IntoPanelPixels, PixelEncoder, PixelFormat};
+use crate::power::PowerState;
+use crate::read::ReadTiming;
 
-use embedded_hal::blocking::delay::DelayMs;
+use core::convert::TryFrom;
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
 use embedded_hal::blocking::spi;
-use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use embedded_hal::PwmPin;
 
 /// ST7735 driver to connect to TFT displays.
-pub struct ST7735<SPI, DC, RST>
+pub struct ST7735<SPI, DC, RST, BL = NoBacklight>
 where
     SPI: spi::Write<u8>,
     DC: OutputPin,
     RST: OutputPin,
+    BL: Backlight,
 {
     /// SPI
     spi: SPI,
@@ -26,21 +62,66 @@ where
     /// Reset pin.
     rst: RST,
 
+    /// Backlight.
+    bl: BL,
+
+    /// Backlight brightness set by the application
+    brightness: u8,
+
     /// Whether the display is RGB (true) or BGR (false)
     rgb: bool,
 
     /// Whether the colours are inverted (true) or not (false)
     inverted: bool,
 
-    /// Global image offset
+    /// Current orientation and mirroring
+    orientation: Orientation,
+    mirror_x: bool,
+    mirror_y: bool,
+
+    /// Global image offset, in portrait orientation
     dx: u16,
     dy: u16,
     width: u32,
     height: u32,
+
+    /// Size of the controller memory, in portrait orientation
+    memory_size: Option<(u16, u16)>,
+
+    /// Largest number of bytes sent in a single SPI write
+    max_transfer: usize,
+
+    /// Color depth of the pixel data
+    pixel_format: PixelFormat,
+
+    /// Controller power state
+    sleeping: bool,
+    display_on: bool,
+    idle: bool,
+
+    /// Whether the optional MISO and TE lines are connected
+    miso_connected: bool,
+    te_connected: bool,
+
+    /// Dummy cycles of read commands
+    read_timing: ReadTiming,
+
+    /// Idle and partial mode frame rates, restored after the low power dashboard mode
+    frame_rates: ([u8; 3], [u8; 6]),
+
+    /// Last address window sent, in controller coordinates
+    window: Option<(u16, u16, u16, u16)>,
+
+    /// Total bytes sent to the display
+    bytes_written: u64,
+
+    /// Receives the text drawn with `draw_text`
+    #[cfg(feature = "text")]
+    text_mirror: Option<fn(u16, u16, &str)>,
 }
 
 /// Display orientation.
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Orientation {
     Portrait = 0x00,
     Landscape = 0x60,
@@ -48,6 +129,145 @@ pub enum Orientation {
     LandscapeSwapped = 0xA0,
 }
 
+/// Gamma correction tables from vendor initialization code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GammaPreset {
+    /// Tables used for ST7735R panels.
+    St7735r,
+    /// Tables used for ST7735B panels.
+    St7735b,
+}
+
+impl GammaPreset {
+    /// Returns the positive and negative polarity gamma tables.
+    pub const fn tables(self) -> ([u8; 16], [u8; 16]) {
+        match self {
+            GammaPreset::St7735r => (
+                [
+                    0x02, 0x1C, 0x07, 0x12, 0x37, 0x32, 0x29, 0x2D, 0x29, 0x25, 0x2B, 0x39, 0x00,
+                    0x01, 0x03, 0x10,
+                ],
+                [
+                    0x03, 0x1D, 0x07, 0x06, 0x2E, 0x2C, 0x29, 0x2D, 0x2E, 0x2E, 0x37, 0x3F, 0x00,
+                    0x00, 0x02, 0x10,
+                ],
+            ),
+            GammaPreset::St7735b => (
+                [
+                    0x09, 0x16, 0x09, 0x20, 0x21, 0x1B, 0x13, 0x19, 0x17, 0x15, 0x1E, 0x2B, 0x04,
+                    0x05, 0x02, 0x0E,
+                ],
+                [
+                    0x0B, 0x14, 0x08, 0x1E, 0x22, 0x1D, 0x18, 0x1E, 0x1B, 0x1A, 0x24, 0x2B, 0x06,
+                    0x06, 0x02, 0x0F,
+                ],
+            ),
+        }
+    }
+}
+
+/// Panel settings that affect how the image looks, applied together with
+/// [`ST7735::apply_tuning`].
+///
+/// Cheap panels can wash out when viewed off-axis. Starting from
+/// [`PanelTuning::DEFAULT`], adjust the gamma tables and VCOM level for the angle the
+/// panel is mounted at and keep the result as a preset for that product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelTuning {
+    /// Positive and negative polarity gamma correction tables.
+    pub gamma: ([u8; 16], [u8; 16]),
+    /// `VMCTR1` VCOM voltage parameter.
+    pub vcom: u8,
+    /// Backlight brightness, where 0 is off and 255 is full brightness.
+    pub brightness: u8,
+}
+
+impl PanelTuning {
+    /// The ST7735R vendor gamma tables and the VCOM level set by [`ST7735::init`], at
+    /// full brightness.
+    ///
+    /// `init` doesn't load gamma tables and leaves the controller's power-on tables in
+    /// place, so applying this changes the look of a freshly initialized panel.
+    pub const DEFAULT: PanelTuning = PanelTuning {
+        gamma: GammaPreset::St7735r.tables(),
+        vcom: 0x0E,
+        brightness: 255,
+    };
+}
+
+impl Orientation {
+    /// Returns whether rows and columns are exchanged in this orientation.
+    pub fn is_landscape(self) -> bool {
+        self as u8 & 0x20 != 0
+    }
+}
+
+/// Order of the color channels of a panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorOrder {
+    Rgb,
+    Bgr,
+}
+
+/// Tearing effect output line mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeMode {
+    /// Pulse during vertical blanking only.
+    VBlankOnly = 0x00,
+    /// Pulse during both vertical and horizontal blanking.
+    VBlankAndHBlank = 0x01,
+}
+
+/// Features available with the current display, see [`ST7735::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Display memory and status can be read back.
+    pub readback: bool,
+    /// Vertical hardware scrolling is available.
+    pub hardware_scroll: bool,
+    /// A tearing effect pin is connected for vsync.
+    pub tearing_pin: bool,
+    /// Partial display mode is available.
+    pub partial_mode: bool,
+    /// The backlight can be switched on and off.
+    pub backlight: bool,
+    /// The backlight brightness can be dimmed.
+    pub brightness: bool,
+    /// A touch controller is available.
+    pub touch: bool,
+}
+
+/// Longest time [`ST7735::wait_for_vblank`] waits for the tearing effect pin, in
+/// microseconds. This is several frames even at the lowest frame rate.
+pub const VBLANK_TIMEOUT_US: u32 = 100_000;
+
+/// Interval at which the tearing effect pin is polled, in microseconds.
+const VBLANK_POLL_US: u16 = 10;
+
+/// Panel setup commands sent by [`ST7735::init`], as command, parameters and delay
+/// in milliseconds.
+///
+/// Copy and adjust this for [`ST7735::init_with_sequence`] when a panel needs
+/// different frame rate or power control values.
+pub const DEFAULT_INIT_SEQUENCE: &[(Instruction, &[u8], u8)] = &[
+    (Instruction::SWRESET, &[], 200),
+    (Instruction::SLPOUT, &[], 200),
+    (Instruction::FRMCTR1, &[0x01, 0x2C, 0x2D], 0),
+    (Instruction::FRMCTR2, &[0x01, 0x2C, 0x2D], 0),
+    (
+        Instruction::FRMCTR3,
+        &[0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D],
+        0,
+    ),
+    (Instruction::INVCTR, &[0x07], 0),
+    (Instruction::PWCTR1, &[0xA2, 0x02, 0x84], 0),
+    (Instruction::PWCTR2, &[0xC5], 0),
+    (Instruction::PWCTR3, &[0x0A, 0x00], 0),
+    (Instruction::PWCTR4, &[0x8A, 0x2A], 0),
+    (Instruction::PWCTR5, &[0x8A, 0xEE], 0),
+    (Instruction::VMCTR1, &[0x0E], 0),
+];
+
 impl<SPI, DC, RST> ST7735<SPI, DC, RST>
 where
     SPI: spi::Write<u8>,
@@ -55,6 +275,9 @@ where
     RST: OutputPin,
 {
     /// Creates a new driver instance that uses hardware SPI.
+    ///
+    /// `width` and `height` are given in portrait orientation; they are swapped when
+    /// the orientation is changed to landscape.
     pub fn new(
         spi: SPI,
         dc: DC,
@@ -64,57 +287,211 @@ where
         width: u32,
         height: u32,
     ) -> Self {
-        let display = ST7735 {
+        ST7735 {
             spi,
             dc,
             rst,
+            bl: NoBacklight,
+            brightness: 255,
             rgb,
             inverted,
+            orientation: Orientation::Portrait,
+            mirror_x: false,
+            mirror_y: false,
             dx: 0,
             dy: 0,
             width,
             height,
-        };
+            memory_size: None,
+            max_transfer: usize::MAX,
+            pixel_format: PixelFormat::Rgb565,
+            sleeping: true,
+            display_on: false,
+            idle: false,
+            miso_connected: false,
+            te_connected: false,
+            read_timing: ReadTiming::ST7735,
+            frame_rates: ([0x01, 0x2C, 0x2D], [0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D]),
+            window: None,
+            bytes_written: 0,
+            #[cfg(feature = "text")]
+            text_mirror: None,
+        }
+    }
 
-        display
+    /// Attaches a backlight pin that is switched on and off by the driver.
+    pub fn with_backlight<BL>(self, pin: BL) -> ST7735<SPI, DC, RST, PinBacklight<BL>>
+    where
+        BL: OutputPin,
+    {
+        self.with_backlight_control(PinBacklight(pin))
+    }
+
+    /// Attaches a PWM backlight channel whose brightness is set by the driver.
+    pub fn with_pwm_backlight<BL>(self, pwm: BL) -> ST7735<SPI, DC, RST, PwmBacklight<BL>>
+    where
+        BL: PwmPin<Duty = u16>,
+    {
+        self.with_backlight_control(PwmBacklight(pwm))
     }
 
+    /// Attaches a custom backlight implementation.
+    pub fn with_backlight_control<BL>(self, bl: BL) -> ST7735<SPI, DC, RST, BL>
+    where
+        BL: Backlight,
+    {
+        ST7735 {
+            spi: self.spi,
+            dc: self.dc,
+            rst: self.rst,
+            bl,
+            brightness: self.brightness,
+            rgb: self.rgb,
+            inverted: self.inverted,
+            orientation: self.orientation,
+            mirror_x: self.mirror_x,
+            mirror_y: self.mirror_y,
+            dx: self.dx,
+            dy: self.dy,
+            width: self.width,
+            height: self.height,
+            memory_size: self.memory_size,
+            max_transfer: self.max_transfer,
+            pixel_format: self.pixel_format,
+            sleeping: self.sleeping,
+            display_on: self.display_on,
+            idle: self.idle,
+            miso_connected: self.miso_connected,
+            te_connected: self.te_connected,
+            read_timing: self.read_timing,
+            frame_rates: self.frame_rates,
+            window: self.window,
+            bytes_written: self.bytes_written,
+            #[cfg(feature = "text")]
+            text_mirror: self.text_mirror,
+        }
+    }
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
     /// Runs commands to initialize the display.
+    ///
+    /// On failure the display can be initialized again, see
+    /// [`init_with_sequence`](Self::init_with_sequence).
     pub fn init<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.init_with_sequence(delay, DEFAULT_INIT_SEQUENCE)
+    }
+
+    /// Initializes the display with custom panel setup commands.
+    ///
+    /// After a hard reset, each command of `sequence` is sent with its parameters,
+    /// followed by its delay in milliseconds. The inversion, orientation, color order
+    /// and pixel format configured on the driver are applied afterwards and the
+    /// display is turned on, so the sequence only needs the panel specific commands,
+    /// see [`DEFAULT_INIT_SEQUENCE`].
+    ///
+    /// If a step fails, the controller is held in reset so it doesn't show garbage,
+    /// and the driver is left ready for another attempt, or to be taken apart with
+    /// [`release`](Self::release).
+    pub fn init_with_sequence<DELAY>(
+        &mut self,
+        delay: &mut DELAY,
+        sequence: &[(Instruction, &[u8], u8)],
+    ) -> Result<(), ()>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        let result = self.run_init_sequence(delay, sequence);
+        if result.is_err() {
+            self.sleeping = true;
+            self.display_on = false;
+            self.idle = false;
+            self.window = None;
+            // Best effort, the pin may be what failed
+            let _ = self.rst.set_low();
+        }
+        result
+    }
+
+    fn run_init_sequence<DELAY>(
+        &mut self,
+        delay: &mut DELAY,
+        sequence: &[(Instruction, &[u8], u8)],
+    ) -> Result<(), ()>
     where
         DELAY: DelayMs<u8>,
     {
         self.hard_reset(delay)?;
-        self.write_command(Instruction::SWRESET, &[])?;
-        delay.delay_ms(200);
-        self.write_command(Instruction::SLPOUT, &[])?;
-        delay.delay_ms(200);
-        self.write_command(Instruction::FRMCTR1, &[0x01, 0x2C, 0x2D])?;
-        self.write_command(Instruction::FRMCTR2, &[0x01, 0x2C, 0x2D])?;
-        self.write_command(Instruction::FRMCTR3, &[0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D])?;
-        self.write_command(Instruction::INVCTR, &[0x07])?;
-        self.write_command(Instruction::PWCTR1, &[0xA2, 0x02, 0x84])?;
-        self.write_command(Instruction::PWCTR2, &[0xC5])?;
-        self.write_command(Instruction::PWCTR3, &[0x0A, 0x00])?;
-        self.write_command(Instruction::PWCTR4, &[0x8A, 0x2A])?;
-        self.write_command(Instruction::PWCTR5, &[0x8A, 0xEE])?;
-        self.write_command(Instruction::VMCTR1, &[0x0E])?;
+        for &(command, params, delay_ms) in sequence {
+            self.send_command(command, params)?;
+            if delay_ms > 0 {
+                delay.delay_ms(delay_ms);
+            }
+        }
         if self.inverted {
             self.write_command(Instruction::INVON, &[])?;
         } else {
             self.write_command(Instruction::INVOFF, &[])?;
         }
-        if self.rgb {
-            self.write_command(Instruction::MADCTL, &[0x00])?;
-        } else {
-            self.write_command(Instruction::MADCTL, &[0x08])?;
-        }
-        self.write_command(Instruction::COLMOD, &[0x05])?;
+        self.write_madctl()?;
+        self.write_command(Instruction::COLMOD, &[self.pixel_format as u8])?;
         self.write_command(Instruction::DISPON, &[])?;
+        self.display_on = true;
         delay.delay_ms(200);
         Ok(())
     }
 
+    /// Returns the SPI bus, the data/command and reset pins and the backlight.
+    pub fn release(self) -> (SPI, DC, RST, BL) {
+        (self.spi, self.dc, self.rst, self.bl)
+    }
+
+    /// Sends a command with its parameters to the display.
+    ///
+    /// The power state reported by [`power_state`](Self::power_state) follows sleep,
+    /// display on/off and idle commands sent this way. Other settings the driver
+    /// keeps track of, like orientation, color order and pixel format, are not
+    /// updated; use their setters instead of sending `MADCTL` or `COLMOD` directly.
+    pub fn send_command(&mut self, command: Instruction, params: &[u8]) -> Result<(), ()> {
+        self.write_command(command, params)?;
+        match command {
+            Instruction::SWRESET => {
+                self.sleeping = true;
+                self.display_on = false;
+                self.idle = false;
+                self.window = None;
+            }
+            Instruction::CASET | Instruction::RASET => self.window = None,
+            Instruction::FRMCTR2 => {
+                if let Ok(params) = <[u8; 3]>::try_from(params) {
+                    self.frame_rates.0 = params;
+                }
+            }
+            Instruction::FRMCTR3 => {
+                if let Ok(params) = <[u8; 6]>::try_from(params) {
+                    self.frame_rates.1 = params;
+                }
+            }
+            Instruction::SLPIN => self.sleeping = true,
+            Instruction::SLPOUT => self.sleeping = false,
+            Instruction::DISPOFF => self.display_on = false,
+            Instruction::DISPON => self.display_on = true,
+            Instruction::IDMOFF => self.idle = false,
+            Instruction::IDMON => self.idle = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
     pub fn hard_reset<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
     where
         DELAY: DelayMs<u8>,
@@ -123,12 +500,232 @@ where
         delay.delay_ms(10);
         self.rst.set_low().map_err(|_| ())?;
         delay.delay_ms(10);
+        self.sleeping = true;
+        self.display_on = false;
+        self.idle = false;
+        self.window = None;
         self.rst.set_high().map_err(|_| ())
     }
 
+    /// Puts the display into sleep mode, stopping the panel and the internal oscillator.
+    ///
+    /// The display memory is retained and can still be written while asleep.
+    pub fn sleep<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.write_command(Instruction::SLPIN, &[])?;
+        self.sleeping = true;
+        delay.delay_ms(120);
+        Ok(())
+    }
+
+    /// Wakes the display up from sleep mode.
+    pub fn wake<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.write_command(Instruction::SLPOUT, &[])?;
+        self.sleeping = false;
+        delay.delay_ms(120);
+        Ok(())
+    }
+
+    /// Turns the panel output on or off without touching the display memory.
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), ()> {
+        if on {
+            self.write_command(Instruction::DISPON, &[])?;
+        } else {
+            self.write_command(Instruction::DISPOFF, &[])?;
+        }
+        self.display_on = on;
+        Ok(())
+    }
+
+    /// Enables or disables idle mode, which reduces the panel to 8 colors.
+    pub fn set_idle(&mut self, idle: bool) -> Result<(), ()> {
+        if idle {
+            self.write_command(Instruction::IDMON, &[])?;
+        } else {
+            self.write_command(Instruction::IDMOFF, &[])?;
+        }
+        self.idle = idle;
+        Ok(())
+    }
+
+    /// Enters partial mode, where only the memory rows from `start` to `end` are shown.
+    pub fn set_partial_area(&mut self, start: u16, end: u16) -> Result<(), ()> {
+        self.write_command(Instruction::PTLAR, &[])?;
+        self.start_data()?;
+        self.write_word(start)?;
+        self.write_word(end)?;
+        self.write_command(Instruction::PTLON, &[])
+    }
+
+    /// Leaves partial mode and shows the whole display again.
+    pub fn set_normal_mode(&mut self) -> Result<(), ()> {
+        self.write_command(Instruction::NORON, &[])
+    }
+
+    /// Returns the current power state of the controller.
+    pub fn power_state(&self) -> PowerState {
+        if self.sleeping {
+            PowerState::Sleep
+        } else if !self.display_on {
+            PowerState::Off
+        } else if self.idle {
+            PowerState::Idle
+        } else {
+            PowerState::On
+        }
+    }
+
+    /// Records which of the optional MISO and TE lines are connected to the display,
+    /// as reported by [`capabilities`](Self::capabilities). Both are assumed to be
+    /// missing until this is called.
+    pub fn set_wiring(&mut self, miso: bool, te: bool) {
+        self.miso_connected = miso;
+        self.te_connected = te;
+    }
+
+    /// Returns the features available with this display and its wiring.
+    ///
+    /// Reading back display memory also needs an SPI bus that implements `Transfer`.
+    /// The ST7735 has no touch controller, so touch is never reported.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            readback: self.miso_connected,
+            hardware_scroll: true,
+            tearing_pin: self.te_connected,
+            partial_mode: true,
+            backlight: self.bl.is_switchable(),
+            brightness: self.bl.is_dimmable(),
+            touch: false,
+        }
+    }
+
+    /// Returns the total number of bytes sent to the display, wrapping on overflow.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Loads one of the preset gamma correction tables.
+    pub fn set_gamma(&mut self, preset: GammaPreset) -> Result<(), ()> {
+        let (positive, negative) = preset.tables();
+        self.set_gamma_custom(&positive, &negative)
+    }
+
+    /// Loads custom positive and negative polarity gamma correction tables.
+    pub fn set_gamma_custom(&mut self, positive: &[u8; 16], negative: &[u8; 16]) -> Result<(), ()> {
+        self.write_command(Instruction::GMCTRP1, positive)?;
+        self.write_command(Instruction::GMCTRN1, negative)
+    }
+
+    /// Enables the tearing effect output line of the controller.
+    pub fn enable_tearing(&mut self, mode: TeMode) -> Result<(), ()> {
+        self.write_command(Instruction::TEON, &[mode as u8])
+    }
+
+    /// Disables the tearing effect output line.
+    pub fn disable_tearing(&mut self) -> Result<(), ()> {
+        self.write_command(Instruction::TEOFF, &[])
+    }
+
+    /// Waits for the start of the next vertical blanking period, signalled by a rising
+    /// edge on the tearing effect pin.
+    ///
+    /// Start writing a frame right after this returns to avoid tearing. Requires the
+    /// tearing effect line to be enabled with [`enable_tearing`](Self::enable_tearing).
+    /// Returns an error if the display is asleep, or if no edge is seen within
+    /// [`VBLANK_TIMEOUT_US`] because the line is disabled or not connected.
+    pub fn wait_for_vblank<TE, DELAY>(&self, te: &TE, delay: &mut DELAY) -> Result<(), ()>
+    where
+        TE: InputPin,
+        DELAY: DelayUs<u16>,
+    {
+        if self.sleeping {
+            return Err(());
+        }
+        let mut waited = 0;
+        let mut poll = |level: bool| -> Result<(), ()> {
+            while te.is_high().map_err(|_| ())? == level {
+                if waited >= VBLANK_TIMEOUT_US {
+                    return Err(());
+                }
+                delay.delay_us(VBLANK_POLL_US);
+                waited += u32::from(VBLANK_POLL_US);
+            }
+            Ok(())
+        };
+        poll(true)?;
+        poll(false)
+    }
+
+    /// Turns the backlight fully on.
+    pub fn backlight_on(&mut self) -> Result<(), ()> {
+        self.set_brightness(255)
+    }
+
+    /// Turns the backlight off.
+    pub fn backlight_off(&mut self) -> Result<(), ()> {
+        self.set_brightness(0)
+    }
+
+    /// Applies gamma tables, VCOM level and backlight brightness in one go.
+    pub fn apply_tuning(&mut self, tuning: &PanelTuning) -> Result<(), ()> {
+        self.set_gamma_custom(&tuning.gamma.0, &tuning.gamma.1)?;
+        self.write_command(Instruction::VMCTR1, &[tuning.vcom])?;
+        self.set_brightness(tuning.brightness)
+    }
+
+    /// Sets the backlight brightness, where 0 is off and 255 is full brightness.
+    ///
+    /// Backlights attached with a plain pin are on for any non-zero level.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), ()> {
+        self.bl.set_brightness(level)?;
+        self.brightness = level;
+        Ok(())
+    }
+
+    /// Switches to a low power mode for mostly static dashboards.
+    ///
+    /// Only the memory rows from `start` to `end` are shown, in 8-color idle mode at the
+    /// lowest frame rate, and the backlight is dimmed to `brightness`. The previous
+    /// brightness is restored by [`exit_low_power_dashboard`](Self::exit_low_power_dashboard).
+    pub fn enter_low_power_dashboard(
+        &mut self,
+        start: u16,
+        end: u16,
+        brightness: u8,
+    ) -> Result<(), ()> {
+        self.write_command(Instruction::FRMCTR2, &[0x0F, 0x3F, 0x3F])?;
+        self.write_command(Instruction::FRMCTR3, &[0x0F, 0x3F, 0x3F, 0x0F, 0x3F, 0x3F])?;
+        self.set_partial_area(start, end)?;
+        self.set_idle(true)?;
+        let previous = self.brightness;
+        self.set_brightness(brightness)?;
+        self.brightness = previous;
+        Ok(())
+    }
+
+    /// Leaves the low power dashboard mode.
+    ///
+    /// The idle and partial mode frame rates are restored to the last `FRMCTR2` and
+    /// `FRMCTR3` values sent with [`send_command`](Self::send_command) or the init
+    /// sequence, or to those of [`DEFAULT_INIT_SEQUENCE`] if none were sent.
+    pub fn exit_low_power_dashboard(&mut self) -> Result<(), ()> {
+        let (idle, partial) = self.frame_rates;
+        self.write_command(Instruction::FRMCTR2, &idle)?;
+        self.write_command(Instruction::FRMCTR3, &partial)?;
+        self.set_idle(false)?;
+        self.set_normal_mode()?;
+        self.set_brightness(self.brightness)
+    }
+
     fn write_command(&mut self, command: Instruction, params: &[u8]) -> Result<(), ()> {
         self.dc.set_low().map_err(|_| ())?;
         self.spi.write(&[command as u8]).map_err(|_| ())?;
+        self.bytes_written = self.bytes_written.wrapping_add(1);
         if !params.is_empty() {
             self.start_data()?;
             self.write_data(params)?;
@@ -141,7 +738,11 @@ where
     }
 
     fn write_data(&mut self, data: &[u8]) -> Result<(), ()> {
-        self.spi.write(data).map_err(|_| ())
+        for chunk in data.chunks(self.max_transfer) {
+            self.spi.write(chunk).map_err(|_| ())?;
+        }
+        self.bytes_written = self.bytes_written.wrapping_add(data.len() as u64);
+        Ok(())
     }
 
     /// Writes a data word to the display.
@@ -149,73 +750,357 @@ where
         self.write_data(&value.to_be_bytes())
     }
 
-    fn write_words_buffered(&mut self, words: impl IntoIterator<Item = u16>) -> Result<(), ()> {
-        let mut buffer = [0; 32];
-        let mut index = 0;
-        for word in words {
-            let as_bytes = word.to_be_bytes();
-            buffer[index] = as_bytes[0];
-            buffer[index + 1] = as_bytes[1];
-            index += 2;
-            if index >= buffer.len() {
-                self.write_data(&buffer)?;
-                index = 0;
-            }
+    /// Sets the display orientation.
+    ///
+    /// Switching between portrait and landscape swaps the display width and height.
+    /// The offsets are transformed to match, see [`set_offset`](Self::set_offset).
+    pub fn set_orientation(&mut self, orientation: &Orientation) -> Result<(), ()> {
+        if orientation.is_landscape() != self.orientation.is_landscape() {
+            core::mem::swap(&mut self.width, &mut self.height);
         }
-        self.write_data(&buffer[0..index])
+        self.orientation = *orientation;
+        self.write_madctl()
     }
 
-    pub fn set_orientation(&mut self, orientation: &Orientation) -> Result<(), ()> {
-        if self.rgb {
-            self.write_command(Instruction::MADCTL, &[*orientation as u8])?;
+    /// Mirrors the image horizontally and/or vertically, in display coordinates.
+    pub fn set_mirror(&mut self, x: bool, y: bool) -> Result<(), ()> {
+        self.mirror_x = x;
+        self.mirror_y = y;
+        self.write_madctl()
+    }
+
+    /// Sets the order of the color channels of the panel.
+    pub fn set_color_order(&mut self, order: ColorOrder) -> Result<(), ()> {
+        self.rgb = order == ColorOrder::Rgb;
+        self.write_madctl()
+    }
+
+    /// Turns color inversion on or off.
+    pub fn set_invert(&mut self, inverted: bool) -> Result<(), ()> {
+        if inverted {
+            self.write_command(Instruction::INVON, &[])?;
+        } else {
+            self.write_command(Instruction::INVOFF, &[])?;
+        }
+        self.inverted = inverted;
+        Ok(())
+    }
+
+    /// Returns the current display orientation.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns the display width and height in the current orientation.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Sends the memory access control value for the current orientation, mirroring
+    /// and color order.
+    fn write_madctl(&mut self) -> Result<(), ()> {
+        let madctl = self.madctl();
+        self.write_command(Instruction::MADCTL, &[madctl])
+    }
+
+    /// Returns the memory access control value for the current orientation, mirroring
+    /// and color order.
+    fn madctl(&self) -> u8 {
+        let mut madctl = self.orientation as u8;
+        // With rows and columns exchanged, the display x axis runs along the panel rows
+        let (mirror_columns, mirror_rows) = if self.orientation.is_landscape() {
+            (self.mirror_y, self.mirror_x)
         } else {
-            self.write_command(Instruction::MADCTL, &[*orientation as u8 | 0x08])?;
+            (self.mirror_x, self.mirror_y)
+        };
+        if mirror_columns {
+            madctl ^= 0x40;
+        }
+        if mirror_rows {
+            madctl ^= 0x80;
         }
+        if !self.rgb {
+            madctl |= 0x08;
+        }
+        madctl
+    }
+
+    /// Returns the address offsets for the current orientation and mirroring.
+    fn window_offset(&self) -> (u16, u16) {
+        let madctl = self.madctl();
+        let (mut column, mut row) = (self.dx, self.dy);
+        if let Some((memory_width, memory_height)) = self.memory_size {
+            let (width, height) = if self.orientation.is_landscape() {
+                (self.height, self.width)
+            } else {
+                (self.width, self.height)
+            };
+            // Mirrored addresses count from the far edge of the controller memory
+            if madctl & 0x40 != 0 {
+                column = u32::from(memory_width).saturating_sub(width + u32::from(self.dx)) as u16;
+            }
+            if madctl & 0x80 != 0 {
+                row = u32::from(memory_height).saturating_sub(height + u32::from(self.dy)) as u16;
+            }
+        }
+        if self.orientation.is_landscape() {
+            (row, column)
+        } else {
+            (column, row)
+        }
+    }
+
+    /// Sets the color depth of the pixel data sent to the display.
+    ///
+    /// Colors passed to the driver are always RGB565 and converted on the fly. Data
+    /// passed to [`write_raw_slice`](Self::write_raw_slice) must already be encoded in
+    /// this format.
+    pub fn set_pixel_format(&mut self, format: PixelFormat) -> Result<(), ()> {
+        self.write_command(Instruction::COLMOD, &[format as u8])?;
+        self.pixel_format = format;
         Ok(())
     }
 
+    /// Returns the color depth of the pixel data sent to the display.
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// Limits the number of bytes handed to the SPI peripheral in a single write.
+    ///
+    /// Longer writes are split into chunks of this size. This is needed for SPI
+    /// drivers with a maximum transfer length, like Linux spidev (4096 bytes by
+    /// default). There is no limit by default.
+    pub fn set_max_transfer_size(&mut self, bytes: usize) {
+        self.max_transfer = bytes.max(1);
+    }
+
     /// Sets the global offset of the displayed image
+    ///
+    /// The offset is given in portrait orientation and swapped in landscape. Panels
+    /// that don't cover the whole controller memory need different offsets when
+    /// mirrored or rotated by 180 degrees; set the memory size with
+    /// [`set_memory_size`](Self::set_memory_size) to have those computed.
     pub fn set_offset(&mut self, dx: u16, dy: u16) {
         self.dx = dx;
         self.dy = dy;
     }
 
-    /// Sets the address window for the display.
-    pub fn set_address_window(&mut self, sx: u16, sy: u16, ex: u16, ey: u16) -> Result<(), ()> {
-        self.write_command(Instruction::CASET, &[])?;
+    /// Sets the size of the controller memory in portrait orientation, usually 132 by
+    /// 162 pixels for panels with an offset.
+    ///
+    /// When set, mirrored axes take their offset from the far edge of the memory.
+    pub fn set_memory_size(&mut self, width: u16, height: u16) {
+        self.memory_size = Some((width, height));
+    }
+
+    /// Defines the vertical scrolling area.
+    ///
+    /// The panel memory is split into a fixed area at the top, a scrolling area and a
+    /// fixed area at the bottom, all given in lines. The three values should add up to
+    /// the number of lines of the controller memory.
+    pub fn set_scroll_region(
+        &mut self,
+        top_fixed: u16,
+        scroll_area: u16,
+        bottom_fixed: u16,
+    ) -> Result<(), ()> {
+        self.write_command(Instruction::VSCRDEF, &[])?;
         self.start_data()?;
-        self.write_word(sx + self.dx)?;
-        self.write_word(ex + self.dx)?;
-        self.write_command(Instruction::RASET, &[])?;
+        self.write_word(top_fixed)?;
+        self.write_word(scroll_area)?;
+        self.write_word(bottom_fixed)
+    }
+
+    /// Sets the memory line shown at the top of the scrolling area.
+    pub fn set_scroll_offset(&mut self, lines: u16) -> Result<(), ()> {
+        self.write_command(Instruction::VSCSAD, &[])?;
         self.start_data()?;
-        self.write_word(sy + self.dy)?;
-        self.write_word(ey + self.dy)
+        self.write_word(lines)
+    }
+
+    /// Sets the address window for the display.
+    ///
+    /// The column or row range is only sent if it differs from the last window set.
+    /// Returns an error if the offset pushes the window past the largest coordinate.
+    pub fn set_address_window(&mut self, sx: u16, sy: u16, ex: u16, ey: u16) -> Result<(), ()> {
+        let (dx, dy) = self.window_offset();
+        let offset = |value: u16, by: u16| value.checked_add(by).ok_or(());
+        let window = (
+            offset(sx, dx)?,
+            offset(sy, dy)?,
+            offset(ex, dx)?,
+            offset(ey, dy)?,
+        );
+        let last = self.window.take();
+        if last.map(|(sx, _, ex, _)| (sx, ex)) != Some((window.0, window.2)) {
+            self.write_command(Instruction::CASET, &[])?;
+            self.start_data()?;
+            self.write_word(window.0)?;
+            self.write_word(window.2)?;
+        }
+        if last.map(|(_, sy, _, ey)| (sy, ey)) != Some((window.1, window.3)) {
+            self.write_command(Instruction::RASET, &[])?;
+            self.start_data()?;
+            self.write_word(window.1)?;
+            self.write_word(window.3)?;
+        }
+        self.window = Some(window);
+        Ok(())
     }
 
     /// Sets a pixel color at the given coords.
     pub fn set_pixel(&mut self, x: u16, y: u16, color: u16) -> Result<(), ()> {
         self.set_address_window(x, y, x, y)?;
-        self.write_command(Instruction::RAMWR, &[])?;
-        self.start_data()?;
-        self.write_word(color)
+        self.write_pixels(core::iter::once(color))
     }
 
     /// Writes pixel colors sequentially into the current drawing window
     pub fn write_pixels<P: IntoIterator<Item = u16>>(&mut self, colors: P) -> Result<(), ()> {
         self.write_command(Instruction::RAMWR, &[])?;
         self.start_data()?;
+        let mut buffer = [0; 3];
+        let mut encoder = PixelEncoder::new(self.pixel_format, &mut buffer);
+        let mut write = |data: &[u8]| self.write_data(data);
         for color in colors {
-            self.write_word(color)?;
+            encoder.push(color, &mut write)?;
         }
-        Ok(())
+        encoder.finish(&mut write)
+    }
+
+    pub fn write_pixels_buffered<P: IntoPanelPixels>(&mut self, colors: P) -> Result<(), ()> {
+        self.write_pixels_with_buffer(colors, &mut [0; 32])
     }
-    pub fn write_pixels_buffered<P: IntoIterator<Item = u16>>(
+
+    /// Writes pixel colors sequentially into the current drawing window, using `buffer`
+    /// to collect the encoded data before each SPI write.
+    ///
+    /// A larger buffer means fewer, larger transfers, which is much faster on
+    /// platforms with a high per-write overhead. Returns an error if the buffer is
+    /// shorter than three bytes.
+    pub fn write_pixels_with_buffer<P: IntoPanelPixels>(
         &mut self,
         colors: P,
+        buffer: &mut [u8],
     ) -> Result<(), ()> {
+        if buffer.len() < 3 {
+            return Err(());
+        }
+        self.write_command(Instruction::RAMWR, &[])?;
+        self.start_data()?;
+        let format = self.pixel_format;
+        colors.write_panel_pixels(format, buffer, |data| self.write_data(data))
+    }
+
+    /// Writes already encoded pixel data into the current drawing window.
+    ///
+    /// The data must match the current [`PixelFormat`], which is big-endian RGB565
+    /// unless changed with [`set_pixel_format`](Self::set_pixel_format).
+    ///
+    /// The data is sent in chunks of the maximum transfer size without any copying.
+    pub fn write_raw_slice(&mut self, data: &[u8]) -> Result<(), ()> {
+        self.write_command(Instruction::RAMWR, &[])?;
+        self.start_data()?;
+        self.write_data(data)
+    }
+
+    /// Draws an image of `w` by `h` pixels from already encoded pixel data.
+    ///
+    /// The data goes to the display as is, so it must match the current
+    /// [`PixelFormat`], which is big-endian RGB565 by default. Returns an error if its
+    /// length doesn't match the image size, or if the image reaches past the largest
+    /// coordinate.
+    pub fn blit(&mut self, x: u16, y: u16, w: u16, h: u16, data: &[u8]) -> Result<(), ()> {
+        if data.len()
+            != self
+                .pixel_format
+                .encoded_len(usize::from(w) * usize::from(h))
+        {
+            return Err(());
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        let (ex, ey) = (
+            x.checked_add(w - 1).ok_or(())?,
+            y.checked_add(h - 1).ok_or(())?,
+        );
+        self.set_address_window(x, y, ex, ey)?;
+        self.write_raw_slice(data)
+    }
+
+    /// Draws a `w` by `h` pixel area one row at a time.
+    ///
+    /// `render` is called with the index of each row, counted from `y`, and fills in
+    /// the RGB565 colors of that row. This allows per-scanline effects like gradients
+    /// or palette shifts without holding the whole area in memory. `row` is the row
+    /// storage and must hold at least `w` pixels.
+    pub fn draw_rows<F>(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        row: &mut [u16],
+        mut render: F,
+    ) -> Result<(), ()>
+    where
+        F: FnMut(u16, &mut [u16]),
+    {
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        let row = row.get_mut(..usize::from(w)).ok_or(())?;
+        let (ex, ey) = (
+            x.checked_add(w - 1).ok_or(())?,
+            y.checked_add(h - 1).ok_or(())?,
+        );
+        self.set_address_window(x, y, ex, ey)?;
         self.write_command(Instruction::RAMWR, &[])?;
         self.start_data()?;
-        self.write_words_buffered(colors)
+        let mut buffer = [0; 96];
+        let mut encoder = PixelEncoder::new(self.pixel_format, &mut buffer);
+        let mut write = |data: &[u8]| self.write_data(data);
+        for index in 0..h {
+            render(index, row);
+            for &color in row.iter() {
+                encoder.push(color, &mut write)?;
+            }
+        }
+        encoder.finish(&mut write)
+    }
+
+    /// Fills a rectangle with a single color.
+    ///
+    /// The color is encoded once into a buffer that is then sent repeatedly, which is
+    /// much faster than sending the pixels one by one. The rectangle is not clipped to
+    /// the display; an error is returned if it reaches past the largest coordinate.
+    pub fn fill_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) -> Result<(), ()> {
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        // A multiple of 6 bytes, so the pattern repeats cleanly in every pixel format
+        let mut buffer = [0; 480];
+        let format = self.pixel_format;
+        let (pattern, len) = pixels::repeat_pattern(format, color);
+        for chunk in buffer.chunks_exact_mut(len) {
+            chunk.copy_from_slice(&pattern[..len]);
+        }
+
+        let (ex, ey) = (
+            x.checked_add(w - 1).ok_or(())?,
+            y.checked_add(h - 1).ok_or(())?,
+        );
+        self.set_address_window(x, y, ex, ey)?;
+        self.write_command(Instruction::RAMWR, &[])?;
+        self.start_data()?;
+        let mut remaining = format.encoded_len(usize::from(w) * usize::from(h));
+        while remaining > 0 {
+            let len = remaining.min(buffer.len());
+            self.write_data(&buffer[..len])?;
+            remaining -= len;
+        }
+        Ok(())
     }
 
     /// Sets pixel colors at the given drawing window
@@ -231,7 +1116,7 @@ where
         self.write_pixels(colors)
     }
 
-    pub fn set_pixels_buffered<P: IntoIterator<Item = u16>>(
+    pub fn set_pixels_buffered<P: IntoPanelPixels>(
         &mut self,
         sx: u16,
         sy: u16,
@@ -242,6 +1127,21 @@ where
         self.set_address_window(sx, sy, ex, ey)?;
         self.write_pixels_buffered(colors)
     }
+
+    /// Sets pixel colors at the given drawing window, using `buffer` to collect the
+    /// encoded data before each SPI write.
+    pub fn set_pixels_with_buffer<P: IntoPanelPixels>(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+        colors: P,
+        buffer: &mut [u8],
+    ) -> Result<(), ()> {
+        self.set_address_window(sx, sy, ex, ey)?;
+        self.write_pixels_with_buffer(colors, buffer)
+    }
 }
 
 #[cfg(feature = "graphics")]
@@ -257,12 +1157,19 @@ use self::embedded_graphics::{
     primitives::Rectangle,
 };
 
+/// Draws [`Rgb565`] colors, the color type used by most embedded-graphics examples.
+///
+/// The channel order of the panel is handled by the `rgb` flag passed to
+/// [`ST7735::new`], so `Rgb565` colors come out correct on both RGB and BGR panels.
+/// To draw other color types, like `Bgr565` images, use
+/// [`DrawTargetExt::color_converted`].
 #[cfg(feature = "graphics")]
-impl<SPI, DC, RST> DrawTarget for ST7735<SPI, DC, RST>
+impl<SPI, DC, RST, BL> DrawTarget for ST7735<SPI, DC, RST, BL>
 where
     SPI: spi::Write<u8>,
     DC: OutputPin,
     RST: OutputPin,
+    BL: Backlight,
 {
     type Error = ();
     type Color = Rgb565;
@@ -271,6 +1178,10 @@ where
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
+        // Pixels following each other in a row continue the previous memory write,
+        // which isn't possible with two pixels per transfer in RGB444
+        let streaming = self.pixel_format != PixelFormat::Rgb444;
+        let mut next = None;
         for Pixel(coord, color) in pixels.into_iter() {
             // Only draw pixels that would be on screen
             if coord.x >= 0
@@ -278,11 +1189,20 @@ where
                 && coord.x < self.width as i32
                 && coord.y < self.height as i32
             {
-                self.set_pixel(
-                    coord.x as u16,
-                    coord.y as u16,
-                    RawU16::from(color).into_inner(),
-                )?;
+                let (x, y) = (coord.x as u16, coord.y as u16);
+                let color = RawU16::from(color).into_inner();
+                if !streaming {
+                    self.set_pixel(x, y, color)?;
+                    continue;
+                }
+                if next != Some((x, y)) {
+                    self.set_address_window(x, y, self.width as u16 - 1, y)?;
+                    self.write_command(Instruction::RAMWR, &[])?;
+                    self.start_data()?;
+                }
+                let (pattern, len) = pixels::repeat_pattern(self.pixel_format, color);
+                self.write_data(&pattern[..len])?;
+                next = Some((x + 1, y));
             }
         }
 
@@ -312,26 +1232,275 @@ where
         Ok(())
     }
 
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let drawable_area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+
+        self.fill_rect(
+            drawable_area.top_left.x as u16,
+            drawable_area.top_left.y as u16,
+            drawable_area.size.width as u16,
+            drawable_area.size.height as u16,
+            RawU16::from(color).into_inner(),
+        )
+    }
+
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        self.set_pixels_buffered(
+        self.fill_rect(
             0,
             0,
-            self.width as u16 - 1,
-            self.height as u16 - 1,
-            core::iter::repeat(RawU16::from(color).into_inner())
-                .take((self.width * self.height) as usize),
+            self.width as u16,
+            self.height as u16,
+            RawU16::from(color).into_inner(),
         )
     }
 }
 
 #[cfg(feature = "graphics")]
-impl<SPI, DC, RST> OriginDimensions for ST7735<SPI, DC, RST>
+impl<SPI, DC, RST, BL> OriginDimensions for ST7735<SPI, DC, RST, BL>
 where
     SPI: spi::Write<u8>,
     DC: OutputPin,
     RST: OutputPin,
+    BL: Backlight,
 {
     fn size(&self) -> Size {
         Size::new(self.width, self.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::mock::{MockDc, MockDelay, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn display(recorder: &Recorder, width: u32, height: u32) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(
+            recorder.spi(),
+            recorder.dc(),
+            MockPin,
+            true,
+            false,
+            width,
+            height,
+        )
+    }
+
+    fn commands(recorder: &Recorder) -> Vec<Instruction> {
+        recorder
+            .transactions()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Command(command, _) => Some(command),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn address_window_skips_unchanged_ranges() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display.set_pixel(1, 2, 0).unwrap();
+        recorder.clear();
+
+        display.set_pixel(3, 2, 0).unwrap();
+        assert_eq!(
+            commands(&recorder),
+            vec![Instruction::CASET, Instruction::RAMWR]
+        );
+        recorder.clear();
+
+        display.set_pixel(3, 2, 0).unwrap();
+        assert_eq!(commands(&recorder), vec![Instruction::RAMWR]);
+    }
+
+    #[test]
+    fn address_window_is_resent_after_window_commands() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display.set_pixel(1, 2, 0).unwrap();
+        display
+            .send_command(Instruction::CASET, &[0, 0, 0, 9])
+            .unwrap();
+        recorder.clear();
+
+        display.set_pixel(1, 2, 0).unwrap();
+        assert_eq!(
+            commands(&recorder),
+            vec![Instruction::CASET, Instruction::RASET, Instruction::RAMWR]
+        );
+    }
+
+    #[test]
+    fn address_window_is_resent_after_reset() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display.set_pixel(1, 2, 0).unwrap();
+        display.send_command(Instruction::SWRESET, &[]).unwrap();
+        recorder.clear();
+
+        display.set_pixel(1, 2, 0).unwrap();
+        assert_eq!(
+            commands(&recorder),
+            vec![Instruction::CASET, Instruction::RASET, Instruction::RAMWR]
+        );
+    }
+
+    #[test]
+    fn address_window_follows_orientation() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 128);
+        display.set_pixel(1, 2, 0).unwrap();
+        display.set_orientation(&Orientation::Landscape).unwrap();
+        recorder.clear();
+
+        // Without offsets the controller addresses stay the same
+        display.set_pixel(1, 2, 0).unwrap();
+        assert_eq!(commands(&recorder), vec![Instruction::RAMWR]);
+
+        // Offsets are given in portrait orientation and swapped in landscape
+        display.set_offset(2, 3);
+        recorder.clear();
+        display.set_pixel(1, 2, 0).unwrap();
+        assert_eq!(
+            recorder.transactions()[..2],
+            [
+                Transaction::Command(Instruction::CASET, vec![0, 4, 0, 4]),
+                Transaction::Command(Instruction::RASET, vec![0, 4, 0, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn rectangles_past_the_largest_coordinate_are_rejected() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        assert_eq!(display.fill_rect(65535, 0, 2, 1, 0), Err(()));
+        assert_eq!(display.fill_rect(0, 65535, 1, 2, 0), Err(()));
+        assert_eq!(display.blit(65535, 0, 2, 1, &[0; 4]), Err(()));
+        assert_eq!(display.fill_rect(65535, 0, 1, 1, 0), Ok(()));
+    }
+
+    #[test]
+    fn short_pixel_buffers_are_rejected() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        let colors = [0xFFFF; 4];
+        assert_eq!(
+            display.write_pixels_with_buffer(colors, &mut [0; 2]),
+            Err(())
+        );
+        assert!(recorder.transactions().is_empty());
+        assert_eq!(
+            display.write_pixels_with_buffer(colors, &mut [0; 3]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn low_power_dashboard_restores_configured_frame_rates() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display
+            .send_command(Instruction::FRMCTR2, &[0x05, 0x3C, 0x3C])
+            .unwrap();
+        display.enter_low_power_dashboard(0, 9, 16).unwrap();
+        recorder.clear();
+
+        display.exit_low_power_dashboard().unwrap();
+        assert_eq!(
+            recorder.transactions()[..2],
+            [
+                Transaction::Command(Instruction::FRMCTR2, vec![0x05, 0x3C, 0x3C]),
+                Transaction::Command(
+                    Instruction::FRMCTR3,
+                    vec![0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_for_vblank_times_out_without_edges() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        // Asleep until initialized
+        assert_eq!(display.wait_for_vblank(&MockPin, &mut MockDelay), Err(()));
+        display.wake(&mut MockDelay).unwrap();
+        assert_eq!(display.wait_for_vblank(&MockPin, &mut MockDelay), Err(()));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn draw_iter_streams_pixels_along_a_row() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb565::RED),
+                Pixel(Point::new(2, 2), Rgb565::BLUE),
+                Pixel(Point::new(5, 2), Rgb565::RED),
+            ])
+            .unwrap();
+        assert_eq!(
+            recorder.transactions(),
+            vec![
+                Transaction::Command(Instruction::CASET, vec![0, 1, 0, 127]),
+                Transaction::Command(Instruction::RASET, vec![0, 2, 0, 2]),
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(vec![0xF8, 0x00, 0x00, 0x1F]),
+                Transaction::Command(Instruction::CASET, vec![0, 5, 0, 127]),
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(vec![0xF8, 0x00]),
+            ]
+        );
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn draw_iter_sends_rgb444_pixels_one_by_one() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display.set_pixel_format(PixelFormat::Rgb444).unwrap();
+        recorder.clear();
+        display
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb565::RED),
+                Pixel(Point::new(2, 2), Rgb565::BLUE),
+            ])
+            .unwrap();
+        assert_eq!(
+            recorder.transactions(),
+            vec![
+                Transaction::Command(Instruction::CASET, vec![0, 1, 0, 1]),
+                Transaction::Command(Instruction::RASET, vec![0, 2, 0, 2]),
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(vec![0xF0, 0x00]),
+                Transaction::Command(Instruction::CASET, vec![0, 2, 0, 2]),
+                Transaction::Command(Instruction::RAMWR, vec![]),
+                Transaction::Data(vec![0x00, 0xF0]),
+            ]
+        );
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn draw_iter_ignores_pixels_off_screen() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder, 128, 160);
+        display
+            .draw_iter([
+                Pixel(Point::new(-1, 0), Rgb565::RED),
+                Pixel(Point::new(128, 0), Rgb565::RED),
+                Pixel(Point::new(0, 160), Rgb565::RED),
+                Pixel(Point::new(65541, 0), Rgb565::RED),
+            ])
+            .unwrap();
+        assert_eq!(recorder.transactions(), vec![]);
+    }
+}
@@ -0,0 +1,299 @@
+//! Bar meters for audio spectrums and VU meters, with peak hold and decay.
+
+use crate::backlight::Backlight;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Direction in which the bars of a [`BarMeter`] grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterDirection {
+    /// Bars side by side growing upwards, as in a spectrum display.
+    Up,
+    /// Bars stacked on top of each other growing to the right, as in a VU meter.
+    Right,
+}
+
+/// `N` bars in a rectangle of the display, updated with one level per bar each frame.
+///
+/// Falling bars and peak markers sink by the decay rate each frame instead of jumping
+/// down, and peaks are held for a number of frames first. Drawing only touches the
+/// parts of each bar that changed since the last frame.
+pub struct BarMeter<const N: usize> {
+    x: u16,
+    y: u16,
+    w: u16,
+    h: u16,
+    direction: MeterDirection,
+    gap: u16,
+    bar_color: u16,
+    peak_color: u16,
+    background: u16,
+    decay: u16,
+    peak_hold: u8,
+    levels: [u16; N],
+    peaks: [u16; N],
+    hold: [u8; N],
+    drawn_levels: [u16; N],
+    drawn_peaks: [u16; N],
+    drawn: bool,
+}
+
+impl<const N: usize> BarMeter<N> {
+    /// Creates a meter filling the `w` by `h` pixel area at `x`, `y`, with green bars
+    /// and white peaks on black, separated by one pixel.
+    ///
+    /// Returns `None` if the area is too narrow to give each bar a pixel.
+    pub fn new(x: u16, y: u16, w: u16, h: u16, direction: MeterDirection) -> Option<Self> {
+        let across = match direction {
+            MeterDirection::Up => w,
+            MeterDirection::Right => h,
+        };
+        if N > usize::from(across) {
+            return None;
+        }
+        Some(BarMeter {
+            x,
+            y,
+            w,
+            h,
+            direction,
+            gap: 1,
+            bar_color: 0x07E0,
+            peak_color: 0xFFFF,
+            background: 0x0000,
+            decay: 2,
+            peak_hold: 15,
+            levels: [0; N],
+            peaks: [0; N],
+            hold: [0; N],
+            drawn_levels: [0; N],
+            drawn_peaks: [0; N],
+            drawn: false,
+        })
+    }
+
+    /// Sets the RGB565 colors of the bars, the peak markers and the background.
+    pub fn with_colors(mut self, bar: u16, peak: u16, background: u16) -> Self {
+        self.bar_color = bar;
+        self.peak_color = peak;
+        self.background = background;
+        self
+    }
+
+    /// Sets the space between bars in pixels.
+    pub fn with_gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets how many pixels falling bars and peaks sink per frame, and for how many
+    /// frames a peak is held before it starts sinking.
+    pub fn with_decay(mut self, pixels_per_frame: u16, peak_hold_frames: u8) -> Self {
+        self.decay = pixels_per_frame.max(1);
+        self.peak_hold = peak_hold_frames;
+        self
+    }
+
+    /// Advances the meter by one frame with new levels from 0.0 to 1.0, one per bar.
+    ///
+    /// Bars without a value in `values` fall towards 0.
+    pub fn update(&mut self, values: &[f32]) {
+        let length = self.length();
+        for bar in 0..N {
+            let value = values.get(bar).copied().unwrap_or(0.0);
+            let target = (value.clamp(0.0, 1.0) * f32::from(length)) as u16;
+            let level = &mut self.levels[bar];
+            *level = target.max(level.saturating_sub(self.decay));
+
+            if *level >= self.peaks[bar] {
+                self.peaks[bar] = *level;
+                self.hold[bar] = self.peak_hold;
+            } else if self.hold[bar] > 0 {
+                self.hold[bar] -= 1;
+            } else {
+                self.peaks[bar] = self.peaks[bar].saturating_sub(self.decay).max(*level);
+            }
+        }
+    }
+
+    /// Draws the changes since the last call, or the whole meter the first time.
+    pub fn draw<SPI, DC, RST, BL>(
+        &mut self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        if !self.drawn {
+            display.fill_rect(self.x, self.y, self.w, self.h, self.background)?;
+            self.drawn_levels = [0; N];
+            self.drawn_peaks = [0; N];
+            self.drawn = true;
+        }
+        for bar in 0..N {
+            let (old_level, level) = (self.drawn_levels[bar], self.levels[bar]);
+            let (old_peak, peak) = (self.drawn_peaks[bar], self.peaks[bar]);
+            if old_level < level {
+                self.fill_segment(display, bar, old_level, level, self.bar_color)?;
+            } else if level < old_level {
+                self.fill_segment(display, bar, level, old_level, self.background)?;
+            }
+            // Peak markers sit on the last pixel of the peak level, above the bar
+            if old_peak != peak && old_peak > level {
+                self.fill_segment(display, bar, old_peak - 1, old_peak, self.background)?;
+            }
+            if peak > level && (peak != old_peak || old_level >= peak) {
+                self.fill_segment(display, bar, peak - 1, peak, self.peak_color)?;
+            }
+            self.drawn_levels[bar] = level;
+            self.drawn_peaks[bar] = peak;
+        }
+        Ok(())
+    }
+
+    /// Makes the next [`draw`](Self::draw) redraw the whole meter.
+    pub fn invalidate(&mut self) {
+        self.drawn = false;
+    }
+
+    /// Returns the length of a full bar in pixels.
+    fn length(&self) -> u16 {
+        match self.direction {
+            MeterDirection::Up => self.h,
+            MeterDirection::Right => self.w,
+        }
+    }
+
+    /// Fills bar `bar` from `from` to `to` pixels, counted from its base.
+    ///
+    /// Returns an error if the segment lies past the largest coordinate.
+    fn fill_segment<SPI, DC, RST, BL>(
+        &self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+        bar: usize,
+        from: u16,
+        to: u16,
+        color: u16,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        let across = match self.direction {
+            MeterDirection::Up => self.w,
+            MeterDirection::Right => self.h,
+        };
+        let slot = across / N.max(1) as u16;
+        let start = slot * bar as u16;
+        let thickness = slot.saturating_sub(self.gap).max(1);
+        match self.direction {
+            MeterDirection::Up => display.fill_rect(
+                self.x.checked_add(start).ok_or(())?,
+                self.y.checked_add(self.h - to).ok_or(())?,
+                thickness,
+                to - from,
+                color,
+            ),
+            MeterDirection::Right => display.fill_rect(
+                self.x.checked_add(from).ok_or(())?,
+                self.y.checked_add(start).ok_or(())?,
+                to - from,
+                thickness,
+                color,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::instruction::Instruction;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec::Vec;
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(
+            recorder.spi(),
+            recorder.dc(),
+            MockPin,
+            true,
+            false,
+            128,
+            160,
+        )
+    }
+
+    /// Returns the window of every memory write as `sx`, `sy`, `ex`, `ey`.
+    fn writes(recorder: &Recorder) -> Vec<(u16, u16, u16, u16)> {
+        let (mut columns, mut rows) = ((0, 0), (0, 0));
+        let mut writes = Vec::new();
+        for transaction in recorder.transactions() {
+            match transaction {
+                Transaction::Command(Instruction::CASET, data) => {
+                    columns = (
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                    )
+                }
+                Transaction::Command(Instruction::RASET, data) => {
+                    rows = (
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                    )
+                }
+                Transaction::Command(Instruction::RAMWR, _) => {
+                    writes.push((columns.0, rows.0, columns.1, rows.1))
+                }
+                _ => {}
+            }
+        }
+        writes
+    }
+
+    #[test]
+    fn bars_must_fit_across_the_area() {
+        assert!(BarMeter::<9>::new(0, 0, 8, 20, MeterDirection::Up).is_none());
+        assert!(BarMeter::<8>::new(0, 0, 8, 20, MeterDirection::Up).is_some());
+        assert!(BarMeter::<9>::new(0, 0, 20, 8, MeterDirection::Right).is_none());
+    }
+
+    #[test]
+    fn falling_bars_leave_a_held_peak() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let mut meter = BarMeter::<4>::new(10, 20, 8, 8, MeterDirection::Up).unwrap();
+        meter.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder), [(10, 20, 17, 27)]);
+
+        // Bars are one pixel wide in two pixel slots, and grow from the bottom
+        meter.update(&[0.0, 0.5]);
+        meter.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[1..], [(12, 24, 12, 27)]);
+
+        // The bar sinks by the decay rate and the peak marker is drawn above it
+        meter.update(&[]);
+        meter.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[2..], [(12, 24, 12, 25), (12, 24, 12, 24)]);
+    }
+
+    #[test]
+    fn segments_past_the_largest_coordinate_are_rejected() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let meter = BarMeter::<4>::new(u16::MAX - 1, 0, 8, 8, MeterDirection::Up).unwrap();
+        assert_eq!(meter.fill_segment(&mut display, 3, 0, 1, 0), Err(()));
+        let meter = BarMeter::<4>::new(0, u16::MAX - 1, 8, 8, MeterDirection::Right).unwrap();
+        assert_eq!(meter.fill_segment(&mut display, 3, 0, 1, 0), Err(()));
+    }
+}
@@ -0,0 +1,217 @@
+//! Recording SPI backend for testing without hardware.
+//!
+//! A [`Recorder`] hands out an SPI bus and a data/command pin that log everything
+//! the driver sends as decoded [`Transaction`]s:
+//!
+//! ```
+//! use st7735_lcd::instruction::Instruction;
+//! use st7735_lcd::mock::{MockPin, Recorder, Transaction};
+//! use st7735_lcd::ST7735;
+//!
+//! let recorder = Recorder::new();
+//! let mut display = ST7735::new(recorder.spi(), recorder.dc(), MockPin, true, false, 128, 160);
+//! display.set_pixel(1, 2, 0xF800).unwrap();
+//!
+//! assert_eq!(
+//!     recorder.transactions(),
+//!     vec![
+//!         Transaction::Command(Instruction::CASET, vec![0, 1, 0, 1]),
+//!         Transaction::Command(Instruction::RASET, vec![0, 2, 0, 2]),
+//!         Transaction::Command(Instruction::RAMWR, vec![]),
+//!         Transaction::Data(vec![0xF8, 0x00]),
+//!     ]
+//! );
+//! ```
+
+extern crate alloc;
+
+use crate::instruction::Instruction;
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::convert::{Infallible, TryFrom};
+
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+/// A decoded exchange with the display controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+    /// A command with its parameter bytes.
+    Command(Instruction, Vec<u8>),
+    /// A command byte that isn't a known [`Instruction`], with its parameter bytes.
+    UnknownCommand(u8, Vec<u8>),
+    /// Pixel data written to display memory after `RAMWR`.
+    Data(Vec<u8>),
+}
+
+#[derive(Default)]
+struct State {
+    data_mode: bool,
+    transactions: Vec<Transaction>,
+    reads: VecDeque<u8>,
+}
+
+impl State {
+    fn record(&mut self, bytes: &[u8]) {
+        if !self.data_mode {
+            for &byte in bytes {
+                let transaction = match Instruction::try_from(byte) {
+                    Ok(Instruction::RAMWR) => {
+                        self.transactions
+                            .push(Transaction::Command(Instruction::RAMWR, Vec::new()));
+                        Transaction::Data(Vec::new())
+                    }
+                    Ok(instruction) => Transaction::Command(instruction, Vec::new()),
+                    Err(()) => Transaction::UnknownCommand(byte, Vec::new()),
+                };
+                self.transactions.push(transaction);
+            }
+            return;
+        }
+        match self.transactions.last_mut() {
+            Some(Transaction::Command(_, data))
+            | Some(Transaction::UnknownCommand(_, data))
+            | Some(Transaction::Data(data)) => data.extend_from_slice(bytes),
+            None => self.transactions.push(Transaction::Data(bytes.to_vec())),
+        }
+    }
+}
+
+/// Records the transactions sent through its [`MockSpi`] and [`MockDc`].
+#[derive(Clone, Default)]
+pub struct Recorder {
+    state: Rc<RefCell<State>>,
+}
+
+impl Recorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an SPI bus that records into this recorder.
+    pub fn spi(&self) -> MockSpi {
+        MockSpi {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Returns a data/command pin that records into this recorder.
+    pub fn dc(&self) -> MockDc {
+        MockDc {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Returns the transactions recorded so far.
+    ///
+    /// A `RAMWR` command is followed by a `Data` entry holding the pixel data, which
+    /// is left out if no data was written.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.state
+            .borrow()
+            .transactions
+            .iter()
+            .filter(|transaction| **transaction != Transaction::Data(Vec::new()))
+            .cloned()
+            .collect()
+    }
+
+    /// Queues bytes to be returned by reads from the SPI bus, in order.
+    pub fn queue_read(&self, bytes: &[u8]) {
+        self.state.borrow_mut().reads.extend(bytes);
+    }
+
+    /// Forgets the transactions recorded so far.
+    pub fn clear(&self) {
+        self.state.borrow_mut().transactions.clear();
+    }
+}
+
+/// SPI bus handed out by a [`Recorder`]. Reads return the bytes queued with
+/// [`Recorder::queue_read`], then zeros.
+pub struct MockSpi {
+    state: Rc<RefCell<State>>,
+}
+
+impl spi::Write<u8> for MockSpi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.state.borrow_mut().record(words);
+        Ok(())
+    }
+}
+
+impl spi::Transfer<u8> for MockSpi {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        let mut state = self.state.borrow_mut();
+        for word in words.iter_mut() {
+            *word = state.reads.pop_front().unwrap_or(0);
+        }
+        Ok(words)
+    }
+}
+
+/// Data/command pin handed out by a [`Recorder`].
+pub struct MockDc {
+    state: Rc<RefCell<State>>,
+}
+
+impl OutputPin for MockDc {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.state.borrow_mut().data_mode = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.state.borrow_mut().data_mode = true;
+        Ok(())
+    }
+}
+
+/// Pin that ignores writes and always reads low, for reset, backlight or TE pins.
+pub struct MockPin;
+
+impl OutputPin for MockPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl InputPin for MockPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// Delay that returns immediately.
+pub struct MockDelay;
+
+impl DelayMs<u8> for MockDelay {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+impl DelayUs<u16> for MockDelay {
+    fn delay_us(&mut self, _us: u16) {}
+}
@@ -0,0 +1,47 @@
+//! Color palettes for telling data series apart.
+
+/// Converts 8 bit per channel RGB to an RGB565 color.
+pub const fn rgb(r: u8, g: u8, b: u8) -> u16 {
+    ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3)
+}
+
+/// The Okabe-Ito palette, which stays distinguishable with the common forms of color
+/// blindness: black, orange, sky blue, bluish green, yellow, blue, vermillion and
+/// reddish purple.
+pub const OKABE_ITO: [u16; 8] = [
+    rgb(0x00, 0x00, 0x00),
+    rgb(0xE6, 0x9F, 0x00),
+    rgb(0x56, 0xB4, 0xE9),
+    rgb(0x00, 0x9E, 0x73),
+    rgb(0xF0, 0xE4, 0x42),
+    rgb(0x00, 0x72, 0xB2),
+    rgb(0xD5, 0x5E, 0x00),
+    rgb(0xCC, 0x79, 0xA7),
+];
+
+/// Returns the color for data series `index`, cycling through the [`OKABE_ITO`]
+/// colors other than black.
+pub fn series_color(index: usize) -> u16 {
+    OKABE_ITO[1 + index % (OKABE_ITO.len() - 1)]
+}
+
+/// Returns color `index` of `count` fully saturated colors with evenly spaced hues.
+///
+/// Evenly spaced hues are easiest to tell apart for normal color vision, but
+/// neighbours can look alike with color blindness; prefer [`series_color`] for up to
+/// seven series.
+pub fn distinct_hue(index: usize, count: usize) -> u16 {
+    let count = count.max(1);
+    // Hue in sixths of the color wheel, with 8 fractional bits
+    let hue = (index % count) * 6 * 256 / count;
+    let rising = (hue % 256) as u8;
+    let falling = 255 - rising;
+    match hue / 256 {
+        0 => rgb(255, rising, 0),
+        1 => rgb(falling, 255, 0),
+        2 => rgb(0, 255, rising),
+        3 => rgb(0, falling, 255),
+        4 => rgb(rising, 0, 255),
+        _ => rgb(255, 0, falling),
+    }
+}
@@ -0,0 +1,249 @@
+//! Conversion of pixel sources into panel data.
+
+/// Color depth of the data sent to the display memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 12 bits per pixel, two pixels packed into three bytes.
+    Rgb444 = 0x03,
+    /// 16 bits per pixel, two bytes per pixel.
+    Rgb565 = 0x05,
+    /// 18 bits per pixel, three bytes per pixel with each channel in the upper bits.
+    Rgb666 = 0x06,
+}
+
+impl PixelFormat {
+    /// Returns the number of bytes needed to send `pixels` pixels in this format.
+    pub fn encoded_len(self, pixels: usize) -> usize {
+        match self {
+            PixelFormat::Rgb444 => pixels * 3 / 2 + pixels % 2,
+            PixelFormat::Rgb565 => pixels * 2,
+            PixelFormat::Rgb666 => pixels * 3,
+        }
+    }
+}
+
+/// Pixel data that can be streamed to the panel.
+///
+/// This is implemented for every `IntoIterator<Item = u16>` of RGB565 colors. Custom
+/// sources, like framebuffers in other formats, can implement it to convert their
+/// pixels in bulk instead of going through a per-pixel iterator.
+pub trait IntoPanelPixels {
+    /// Encodes the pixels in `format` and hands the bytes to `write` in chunks.
+    ///
+    /// `buffer` is scratch space provided by the driver; implementations that already
+    /// hold encoded data may pass it to `write` directly instead.
+    fn write_panel_pixels<W>(
+        self,
+        format: PixelFormat,
+        buffer: &mut [u8],
+        write: W,
+    ) -> Result<(), ()>
+    where
+        W: FnMut(&[u8]) -> Result<(), ()>;
+}
+
+impl<I> IntoPanelPixels for I
+where
+    I: IntoIterator<Item = u16>,
+{
+    fn write_panel_pixels<W>(
+        self,
+        format: PixelFormat,
+        buffer: &mut [u8],
+        mut write: W,
+    ) -> Result<(), ()>
+    where
+        W: FnMut(&[u8]) -> Result<(), ()>,
+    {
+        let mut encoder = PixelEncoder::new(format, buffer);
+        for color in self {
+            encoder.push(color, &mut write)?;
+        }
+        encoder.finish(&mut write)
+    }
+}
+
+/// Encodes RGB565 colors into a buffer in any [`PixelFormat`], writing it out when full.
+///
+/// The buffer must be at least three bytes long, or pushing colors fails.
+pub struct PixelEncoder<'a> {
+    format: PixelFormat,
+    buffer: &'a mut [u8],
+    index: usize,
+    pending: Option<u16>,
+}
+
+impl<'a> PixelEncoder<'a> {
+    /// Creates an encoder that collects data in `buffer`.
+    pub fn new(format: PixelFormat, buffer: &'a mut [u8]) -> Self {
+        PixelEncoder {
+            format,
+            buffer,
+            index: 0,
+            pending: None,
+        }
+    }
+
+    /// Adds an RGB565 color, handing the buffer to `write` whenever it fills up.
+    ///
+    /// Returns an error if the buffer is shorter than three bytes.
+    pub fn push<W>(&mut self, color: u16, write: &mut W) -> Result<(), ()>
+    where
+        W: FnMut(&[u8]) -> Result<(), ()>,
+    {
+        if self.buffer.len() < 3 {
+            return Err(());
+        }
+        if self.index + 3 > self.buffer.len() {
+            write(&self.buffer[..self.index])?;
+            self.index = 0;
+        }
+        match self.format {
+            PixelFormat::Rgb565 => {
+                let as_bytes = color.to_be_bytes();
+                self.buffer[self.index] = as_bytes[0];
+                self.buffer[self.index + 1] = as_bytes[1];
+                self.index += 2;
+            }
+            PixelFormat::Rgb666 => {
+                self.buffer[self.index..self.index + 3].copy_from_slice(&to_666(color));
+                self.index += 3;
+            }
+            PixelFormat::Rgb444 => match self.pending.take() {
+                None => self.pending = Some(color),
+                Some(first) => {
+                    let first = to_444(first);
+                    let second = to_444(color);
+                    self.buffer[self.index] = (first >> 4) as u8;
+                    self.buffer[self.index + 1] = ((first << 4) as u8) | (second >> 8) as u8;
+                    self.buffer[self.index + 2] = second as u8;
+                    self.index += 3;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Writes out any remaining data, padding an unpaired RGB444 pixel.
+    pub fn finish<W>(mut self, write: &mut W) -> Result<(), ()>
+    where
+        W: FnMut(&[u8]) -> Result<(), ()>,
+    {
+        if let Some(last) = self.pending.take() {
+            if self.index + 2 > self.buffer.len() {
+                write(&self.buffer[..self.index])?;
+                self.index = 0;
+            }
+            let last = to_444(last);
+            self.buffer[self.index] = (last >> 4) as u8;
+            self.buffer[self.index + 1] = (last << 4) as u8;
+            self.index += 2;
+        }
+        if self.index > 0 {
+            write(&self.buffer[..self.index])?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a color as the shortest byte pattern that repeats for a run of that color.
+pub(crate) fn repeat_pattern(format: PixelFormat, color: u16) -> ([u8; 3], usize) {
+    match format {
+        PixelFormat::Rgb444 => {
+            let color = to_444(color);
+            (
+                [
+                    (color >> 4) as u8,
+                    (color << 4) as u8 | (color >> 8) as u8,
+                    color as u8,
+                ],
+                3,
+            )
+        }
+        PixelFormat::Rgb565 => {
+            let [high, low] = color.to_be_bytes();
+            ([high, low, 0], 2)
+        }
+        PixelFormat::Rgb666 => (to_666(color), 3),
+    }
+}
+
+/// Splits an RGB565 color into its 5, 6 and 5 bit channels.
+fn split(color: u16) -> (u8, u8, u8) {
+    (
+        (color >> 11) as u8,
+        ((color >> 5) & 0x3F) as u8,
+        (color & 0x1F) as u8,
+    )
+}
+
+/// Converts an RGB565 color to three RGB666 bytes with each channel in the upper bits.
+fn to_666(color: u16) -> [u8; 3] {
+    let (r, g, b) = split(color);
+    [(r << 3) | (r >> 2), g << 2, (b << 3) | (b >> 2)]
+}
+
+/// Converts an RGB565 color to a 12 bit RGB444 value.
+fn to_444(color: u16) -> u16 {
+    let (r, g, b) = split(color);
+    (u16::from(r >> 1) << 8) | (u16::from(g >> 2) << 4) | u16::from(b >> 1)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    fn encode(format: PixelFormat, colors: &[u16], buffer_len: usize) -> Vec<u8> {
+        let mut buffer = [0; 8];
+        let mut encoder = PixelEncoder::new(format, &mut buffer[..buffer_len]);
+        let mut encoded = Vec::new();
+        let mut write = |data: &[u8]| {
+            encoded.extend_from_slice(data);
+            Ok(())
+        };
+        for &color in colors {
+            encoder.push(color, &mut write).unwrap();
+        }
+        encoder.finish(&mut write).unwrap();
+        encoded
+    }
+
+    #[test]
+    fn rgb444_packs_pixel_pairs() {
+        assert_eq!(
+            encode(PixelFormat::Rgb444, &[0xF800, 0x001F], 8),
+            [0xF0, 0x00, 0x0F]
+        );
+    }
+
+    #[test]
+    fn rgb444_pads_an_odd_pixel() {
+        assert_eq!(encode(PixelFormat::Rgb444, &[0xFFFF], 8), [0xFF, 0xF0]);
+        let colors = [0xFFFF, 0x0000, 0xF800];
+        let expected = [0xFF, 0xF0, 0x00, 0xF0, 0x00];
+        // Small buffers are written out between pixel pairs
+        for buffer_len in 3..=8 {
+            assert_eq!(encode(PixelFormat::Rgb444, &colors, buffer_len), expected);
+        }
+        assert_eq!(PixelFormat::Rgb444.encoded_len(3), expected.len());
+    }
+
+    #[test]
+    fn short_buffers_are_rejected() {
+        let mut buffer = [0; 2];
+        let mut encoder = PixelEncoder::new(PixelFormat::Rgb565, &mut buffer);
+        assert_eq!(encoder.push(0xFFFF, &mut |_: &[u8]| Ok(())), Err(()));
+    }
+
+    #[test]
+    fn rgb666_widens_channels() {
+        assert_eq!(
+            encode(PixelFormat::Rgb666, &[0xFFFF, 0x07E0], 8),
+            [0xFF, 0xFC, 0xFF, 0x00, 0xFC, 0x00]
+        );
+    }
+}
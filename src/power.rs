@@ -0,0 +1,114 @@
+//! Power consumption estimation.
+
+/// Power state of the display controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// Panel on, showing all colors.
+    On,
+    /// Panel on in 8-color idle mode.
+    Idle,
+    /// Controller awake with the panel output turned off.
+    Off,
+    /// Controller in sleep mode.
+    Sleep,
+}
+
+/// Power figures of a display module, used to estimate its energy use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerProfile {
+    /// Power draw with the panel on, in microwatts.
+    pub on_uw: u32,
+    /// Power draw in idle mode, in microwatts.
+    pub idle_uw: u32,
+    /// Power draw with the panel output off, in microwatts.
+    pub off_uw: u32,
+    /// Power draw in sleep mode, in microwatts.
+    pub sleep_uw: u32,
+    /// Energy spent per byte sent over SPI, in nanojoules.
+    pub transfer_nj_per_byte: u32,
+}
+
+impl PowerProfile {
+    fn power_uw(&self, state: PowerState) -> u32 {
+        match state {
+            PowerState::On => self.on_uw,
+            PowerState::Idle => self.idle_uw,
+            PowerState::Off => self.off_uw,
+            PowerState::Sleep => self.sleep_uw,
+        }
+    }
+}
+
+/// Estimated energy use over the metered time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnergyReport {
+    /// Time spent with the panel on, in milliseconds.
+    pub on_ms: u64,
+    /// Time spent in idle mode, in milliseconds.
+    pub idle_ms: u64,
+    /// Time spent with the panel output off, in milliseconds.
+    pub off_ms: u64,
+    /// Time spent in sleep mode, in milliseconds.
+    pub sleep_ms: u64,
+    /// Bytes sent to the display.
+    pub bytes_transferred: u64,
+    /// Estimated energy used, in microjoules.
+    pub energy_uj: u64,
+}
+
+/// Accumulates time per power state and transferred bytes into an energy estimate.
+///
+/// The driver has no clock, so the application samples the meter periodically with
+/// the driver's `power_state()` and `bytes_written()` and the time elapsed since the
+/// previous sample.
+pub struct EnergyMeter {
+    profile: PowerProfile,
+    report: EnergyReport,
+    last_bytes: Option<u64>,
+    energy_nj: u64,
+}
+
+impl EnergyMeter {
+    /// Creates a meter using the given power figures.
+    pub fn new(profile: PowerProfile) -> Self {
+        EnergyMeter {
+            profile,
+            report: EnergyReport::default(),
+            last_bytes: None,
+            energy_nj: 0,
+        }
+    }
+
+    /// Accounts `elapsed_ms` spent in `state` and the bytes written since the last sample.
+    ///
+    /// `bytes_written` is the driver's running byte counter; the first sample only
+    /// records its starting value.
+    pub fn sample(&mut self, state: PowerState, bytes_written: u64, elapsed_ms: u32) {
+        let elapsed_ms = u64::from(elapsed_ms);
+        match state {
+            PowerState::On => self.report.on_ms += elapsed_ms,
+            PowerState::Idle => self.report.idle_ms += elapsed_ms,
+            PowerState::Off => self.report.off_ms += elapsed_ms,
+            PowerState::Sleep => self.report.sleep_ms += elapsed_ms,
+        }
+        let bytes = bytes_written.wrapping_sub(self.last_bytes.unwrap_or(bytes_written));
+        self.last_bytes = Some(bytes_written);
+        self.report.bytes_transferred += bytes;
+
+        // uW * ms = nJ
+        self.energy_nj += u64::from(self.profile.power_uw(state)) * elapsed_ms
+            + u64::from(self.profile.transfer_nj_per_byte) * bytes;
+        self.report.energy_uj = self.energy_nj / 1000;
+    }
+
+    /// Returns the estimate accumulated so far.
+    pub fn report(&self) -> EnergyReport {
+        self.report
+    }
+
+    /// Clears the accumulated estimate.
+    pub fn reset(&mut self) {
+        self.report = EnergyReport::default();
+        self.energy_nj = 0;
+    }
+}
@@ -0,0 +1,220 @@
+//! Reading back from the display controller.
+//!
+//! Reads need a MISO line wired to the controller's serial data output, and chip
+//! select must stay asserted for the whole command.
+
+use crate::backlight::Backlight;
+use crate::instruction::Instruction;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Dummy clock cycles the controller inserts before the data of read commands.
+///
+/// Clones of the ST7735 and related controllers differ in these, which shows as
+/// reads shifted by some bits. The SPI clock for reads can't be set through
+/// embedded-hal, so lower it in the HAL around reads if the controller needs that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadTiming {
+    /// Dummy bits before the data of `RDDID`.
+    pub id_dummy_bits: u8,
+    /// Dummy bits before the data of `RDDST`.
+    pub status_dummy_bits: u8,
+    /// Dummy bytes before the pixel data of `RAMRD`.
+    pub ram_dummy_bytes: u8,
+}
+
+impl ReadTiming {
+    /// Timing of the ST7735 family, the default.
+    pub const ST7735: ReadTiming = ReadTiming {
+        id_dummy_bits: 1,
+        status_dummy_bits: 1,
+        ram_dummy_bytes: 1,
+    };
+
+    /// Timing of controllers like the ILI9341 that send a full dummy byte first.
+    pub const ILI9341: ReadTiming = ReadTiming {
+        id_dummy_bits: 8,
+        status_dummy_bits: 8,
+        ram_dummy_bytes: 1,
+    };
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8> + spi::Transfer<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Sets the dummy cycles expected before read data, [`ReadTiming::ST7735`] by
+    /// default.
+    pub fn set_read_timing(&mut self, timing: ReadTiming) {
+        self.read_timing = timing;
+    }
+
+    /// Reads the manufacturer ID, module version and module ID of the controller.
+    pub fn read_id(&mut self) -> Result<[u8; 3], ()> {
+        let mut id = [0; 3];
+        let dummy_bits = self.read_timing.id_dummy_bits;
+        self.read_command(Instruction::RDDID, dummy_bits, &mut id)?;
+        Ok(id)
+    }
+
+    /// Reads the 32 bit display status register.
+    pub fn read_status(&mut self) -> Result<u32, ()> {
+        let mut status = [0; 4];
+        let dummy_bits = self.read_timing.status_dummy_bits;
+        self.read_command(Instruction::RDDST, dummy_bits, &mut status)?;
+        Ok(u32::from_be_bytes(status))
+    }
+
+    /// Reads the RGB565 colors of the given drawing window into `colors`.
+    ///
+    /// The controller sends pixels as RGB666, which are reduced to RGB565. Reads as
+    /// many pixels as fit into `colors`, up to the size of the window. Returns an
+    /// error if the window ends before it starts.
+    pub fn read_pixels(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+        colors: &mut [u16],
+    ) -> Result<(), ()> {
+        if ex < sx || ey < sy {
+            return Err(());
+        }
+        let pixels =
+            (usize::from(ex) - usize::from(sx) + 1) * (usize::from(ey) - usize::from(sy) + 1);
+        let len = pixels.min(colors.len());
+        let colors = &mut colors[..len];
+        self.set_address_window(sx, sy, ex, ey)?;
+
+        self.write_command(Instruction::RAMRD, &[])?;
+        self.start_data()?;
+        for _ in 0..self.read_timing.ram_dummy_bytes {
+            self.spi.transfer(&mut [0]).map_err(|_| ())?;
+        }
+        let mut buffer = [0; 48];
+        for chunk in colors.chunks_mut(buffer.len() / 3) {
+            let bytes = &mut buffer[..chunk.len() * 3];
+            bytes.fill(0);
+            let bytes = self.spi.transfer(bytes).map_err(|_| ())?;
+            for (color, rgb) in chunk.iter_mut().zip(bytes.chunks_exact(3)) {
+                *color = (u16::from(rgb[0] >> 3) << 11)
+                    | (u16::from(rgb[1] >> 2) << 5)
+                    | u16::from(rgb[2] >> 3);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a read command and reads `data.len()` bytes after skipping `dummy_bits`
+    /// clock cycles.
+    fn read_command(
+        &mut self,
+        command: Instruction,
+        dummy_bits: u8,
+        data: &mut [u8],
+    ) -> Result<(), ()> {
+        let skip = usize::from(dummy_bits / 8);
+        let shift = dummy_bits % 8;
+        let len = skip + data.len() + usize::from(shift > 0);
+        let mut buffer = [0; 8];
+        let buffer = buffer.get_mut(..len).ok_or(())?;
+
+        self.write_command(command, &[])?;
+        self.start_data()?;
+        let read = self.spi.transfer(buffer).map_err(|_| ())?;
+        let read = &read[skip..];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = if shift == 0 {
+                read[index]
+            } else {
+                (read[index] << shift) | (read[index + 1] >> (8 - shift))
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadTiming;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder};
+    use crate::ST7735;
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(
+            recorder.spi(),
+            recorder.dc(),
+            MockPin,
+            true,
+            false,
+            128,
+            160,
+        )
+    }
+
+    #[test]
+    fn read_id_skips_a_dummy_bit() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        // 0x7C 0x89 0xF0 shifted right by one dummy bit
+        recorder.queue_read(&[0x3E, 0x44, 0xF8, 0x00]);
+        assert_eq!(display.read_id(), Ok([0x7C, 0x89, 0xF0]));
+    }
+
+    #[test]
+    fn read_status_skips_a_dummy_bit() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        recorder.queue_read(&[0x80, 0x00, 0x00, 0x00, 0x80]);
+        assert_eq!(display.read_status(), Ok(0x0000_0001));
+    }
+
+    #[test]
+    fn ili9341_timing_skips_a_dummy_byte() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        display.set_read_timing(ReadTiming::ILI9341);
+        recorder.queue_read(&[0xFF, 0x7C, 0x89, 0xF0]);
+        assert_eq!(display.read_id(), Ok([0x7C, 0x89, 0xF0]));
+    }
+
+    #[test]
+    fn read_pixels_skips_dummy_bytes() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        display.set_read_timing(ReadTiming {
+            ram_dummy_bytes: 2,
+            ..ReadTiming::ST7735
+        });
+        recorder.queue_read(&[0xFF, 0xFF, 0x00, 0x00, 0xF8]);
+        let mut colors = [0; 1];
+        display.read_pixels(0, 0, 0, 0, &mut colors).unwrap();
+        assert_eq!(colors, [0x001F]);
+    }
+
+    #[test]
+    fn read_pixels_reduces_rgb666() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        recorder.queue_read(&[0x00, 0xFC, 0x00, 0x00, 0x00, 0xFC, 0x00]);
+        let mut colors = [0; 3];
+        display.read_pixels(0, 0, 1, 0, &mut colors).unwrap();
+        // Only the two pixels of the window are read
+        assert_eq!(colors, [0xF800, 0x07E0, 0]);
+    }
+
+    #[test]
+    fn read_pixels_rejects_inverted_windows() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        assert_eq!(display.read_pixels(5, 0, 4, 0, &mut [0; 4]), Err(()));
+        assert_eq!(display.read_pixels(0, 5, 0, 4, &mut [0; 4]), Err(()));
+        assert_eq!(display.read_pixels(0, 0, 65535, 0, &mut [0; 4]), Ok(()));
+    }
+}
@@ -0,0 +1,115 @@
+//! Drawing into a part of the display with its own coordinates.
+
+use crate::backlight::Backlight;
+use crate::ST7735;
+
+use embedded_graphics::{draw_target::DrawTarget, prelude::*, primitives::Rectangle};
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// A view of a rectangle of a draw target, with the origin at its top left corner.
+///
+/// Drawing outside of the rectangle is clipped before it reaches the target, and
+/// rectangle fills stay rectangle fills, so widgets can draw in their own coordinates
+/// without slowing down. Unlike [`DrawTargetExt::cropped`], which only moves the
+/// origin, nothing is drawn outside of the rectangle.
+pub struct Region<'a, D> {
+    target: &'a mut D,
+    area: Rectangle,
+}
+
+impl<'a, D> Region<'a, D>
+where
+    D: DrawTarget,
+{
+    /// Creates a view of `area` of `target`, limited to the bounds of `target`.
+    pub fn new(target: &'a mut D, area: Rectangle) -> Self {
+        let area = area.intersection(&target.bounding_box());
+        Region { target, area }
+    }
+
+    /// Returns a view of `area` of this region, in the coordinates of this region.
+    pub fn region(&mut self, area: Rectangle) -> Region<'_, Self> {
+        Region::new(self, area)
+    }
+
+    /// Returns the area of the target covered by this region.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<D> DrawTarget for Region<'_, D>
+where
+    D: DrawTarget,
+{
+    type Error = D::Error;
+    type Color = D::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord + area.top_left, color))
+                .filter(|Pixel(coord, _)| area.contains(*coord)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let area = area.translate(self.area.top_left);
+        let drawable_area = area.intersection(&self.area);
+        if drawable_area == area {
+            self.target.fill_contiguous(&area, colors)
+        } else if drawable_area.size != Size::zero() {
+            self.target.fill_contiguous(
+                &drawable_area,
+                area.points()
+                    .zip(colors)
+                    .filter(|(pos, _color)| drawable_area.contains(*pos))
+                    .map(|(_pos, color)| color),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.translate(self.area.top_left).intersection(&self.area);
+        if area.size == Size::zero() {
+            return Ok(());
+        }
+        self.target.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(&self.area, color)
+    }
+}
+
+impl<D> OriginDimensions for Region<'_, D> {
+    fn size(&self) -> Size {
+        self.area.size
+    }
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Returns a view of `area` of the display, see [`Region`].
+    ///
+    /// To only move the origin without clipping, use [`DrawTargetExt::translated`].
+    pub fn region(&mut self, area: Rectangle) -> Region<'_, Self> {
+        Region::new(self, area)
+    }
+}
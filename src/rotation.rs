@@ -0,0 +1,113 @@
+//! Automatic rotation driven by an orientation sensor.
+
+use crate::backlight::Backlight;
+use crate::{Orientation, ST7735};
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// A sensor reporting which way up the display is held, like an accelerometer.
+pub trait OrientationSource {
+    /// Returns the orientation the display should use, or `None` when the reading is
+    /// ambiguous, for example when the device lies flat.
+    fn read_orientation(&mut self) -> Result<Option<Orientation>, ()>;
+}
+
+/// Picks an orientation from the gravity vector measured by an accelerometer.
+///
+/// `x` and `y` are given along the display's portrait axes, with `x` pointing right
+/// and `y` pointing down. One axis must clearly dominate, otherwise `None` is
+/// returned so that tilted or flat readings don't cause a rotation.
+pub fn orientation_from_gravity(x: i16, y: i16) -> Option<Orientation> {
+    let (ax, ay) = (i32::from(x).abs(), i32::from(y).abs());
+    if ay > 2 * ax {
+        if y > 0 {
+            Some(Orientation::Portrait)
+        } else {
+            Some(Orientation::PortraitSwapped)
+        }
+    } else if ax > 2 * ay {
+        if x > 0 {
+            Some(Orientation::Landscape)
+        } else {
+            Some(Orientation::LandscapeSwapped)
+        }
+    } else {
+        None
+    }
+}
+
+/// Rotates a display to follow an [`OrientationSource`].
+///
+/// A new orientation is only applied after the source reported it for a number of
+/// consecutive polls, so that shaking the device doesn't make the display flip.
+pub struct AutoRotate<S> {
+    source: S,
+    current: Orientation,
+    candidate: Option<Orientation>,
+    count: u8,
+    samples: u8,
+}
+
+impl<S> AutoRotate<S>
+where
+    S: OrientationSource,
+{
+    /// Creates a manager starting in `current` that rotates after `samples` matching readings.
+    pub fn new(source: S, current: Orientation, samples: u8) -> Self {
+        AutoRotate {
+            source,
+            current,
+            candidate: None,
+            count: 0,
+            samples: samples.max(1),
+        }
+    }
+
+    /// Returns the orientation currently applied.
+    pub fn orientation(&self) -> Orientation {
+        self.current
+    }
+
+    /// Reads the source once and rotates the display if the orientation settled.
+    ///
+    /// Returns the new orientation when the display was rotated.
+    pub fn poll<SPI, DC, RST, BL>(
+        &mut self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+    ) -> Result<Option<Orientation>, ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        let reading = match self.source.read_orientation()? {
+            Some(reading) if reading != self.current => reading,
+            _ => {
+                self.candidate = None;
+                self.count = 0;
+                return Ok(None);
+            }
+        };
+        if self.candidate == Some(reading) {
+            self.count += 1;
+        } else {
+            self.candidate = Some(reading);
+            self.count = 1;
+        }
+        if self.count < self.samples {
+            return Ok(None);
+        }
+        display.set_orientation(&reading)?;
+        self.current = reading;
+        self.candidate = None;
+        self.count = 0;
+        Ok(Some(reading))
+    }
+
+    /// Returns the orientation source.
+    pub fn release(self) -> S {
+        self.source
+    }
+}
@@ -0,0 +1,529 @@
+//! Lines and shapes drawn into a [`FrameBuffer`].
+
+use crate::framebuffer::{BufferProvider, FrameBuffer};
+
+/// How lines are drawn by [`FrameBuffer::draw_line`] and
+/// [`FrameBuffer::draw_polyline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stroke {
+    /// RGB565 line color.
+    pub color: u16,
+    /// Line width in pixels.
+    pub width: u16,
+    /// Length of the drawn parts of a dashed line, or 0 for a solid line.
+    ///
+    /// Dash and gap lengths are counted in pixels along the longer axis of each line.
+    pub dash: u16,
+    /// Length of the gaps of a dashed line.
+    pub gap: u16,
+}
+
+impl Stroke {
+    /// A solid line.
+    pub const fn solid(color: u16, width: u16) -> Self {
+        Stroke {
+            color,
+            width,
+            dash: 0,
+            gap: 0,
+        }
+    }
+
+    /// A dashed line, drawn for `dash` pixels and left out for `gap` pixels in turn.
+    ///
+    /// Use a `dash` of 1 for a dotted line.
+    pub const fn dashed(color: u16, width: u16, dash: u16, gap: u16) -> Self {
+        Stroke {
+            color,
+            width,
+            dash,
+            gap,
+        }
+    }
+
+    /// Returns whether the dash pattern is drawn at `phase` pixels from its start.
+    fn is_drawn(&self, phase: u32) -> bool {
+        if self.dash == 0 || self.gap == 0 {
+            return true;
+        }
+        phase % (u32::from(self.dash) + u32::from(self.gap)) < u32::from(self.dash)
+    }
+}
+
+impl<B> FrameBuffer<'_, B>
+where
+    B: BufferProvider,
+{
+    /// Draws a line from `start` to `end`, both included, clipped to the frame.
+    pub fn draw_line(&mut self, start: (i32, i32), end: (i32, i32), stroke: &Stroke) {
+        self.stroke_segment(start, end, stroke, 0);
+    }
+
+    /// Draws lines through all of `points`, with rounded corners for wide lines and
+    /// dashes continuing around corners.
+    pub fn draw_polyline(&mut self, points: &[(i32, i32)], stroke: &Stroke) {
+        let mut phase = 0;
+        for (index, segment) in points.windows(2).enumerate() {
+            if index > 0 && stroke.width > 1 && stroke.is_drawn(phase) {
+                self.fill_circle(segment[0], stroke.width / 2, stroke.color);
+            }
+            phase = self.stroke_segment(segment[0], segment[1], stroke, phase);
+        }
+    }
+
+    /// Fills a circle of the given radius around `center`, clipped to the frame.
+    pub fn fill_circle(&mut self, center: (i32, i32), radius: u16, color: u16) {
+        let radius = i64::from(radius);
+        let (cx, cy) = (i64::from(center.0), i64::from(center.1));
+        // Keep span ends within the frame, and just outside it for empty spans
+        let width = i64::from(self.width());
+        let column = |x: i64| x.clamp(-1, width) as i32;
+        let top = (cy - radius).max(0);
+        let bottom = (cy + radius).min(i64::from(self.height()) - 1);
+        for y in top..=bottom {
+            let dy = y - cy;
+            let half = isqrt((radius * radius - dy * dy) as u64) as i64;
+            self.fill_span(column(cx - half), column(cx + half), y as i32, color);
+        }
+    }
+
+    /// Fills the polygon with the corners `points`, clipped to the frame.
+    ///
+    /// Pixels are filled if their center is inside the polygon by the even-odd rule,
+    /// so adjacent polygons sharing an edge don't overlap. Returns an error if more
+    /// than 32 edges cross the same row, in which case the polygon is only partly
+    /// filled.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], color: u16) -> Result<(), ()> {
+        let top = points.iter().map(|point| point.1).min().unwrap_or(0).max(0);
+        let bottom = points
+            .iter()
+            .map(|point| point.1)
+            .max()
+            .unwrap_or(0)
+            .min(i32::from(self.height()));
+        for y in top..bottom {
+            let mut crossings = [0i32; 32];
+            let mut count = 0;
+            let edges = points.iter().zip(points.iter().cycle().skip(1));
+            for (&(x0, y0), &(x1, y1)) in edges {
+                if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                    let x = i64::from(x0)
+                        + (i64::from(y) - i64::from(y0)) * (i64::from(x1) - i64::from(x0))
+                            / (i64::from(y1) - i64::from(y0));
+                    *crossings.get_mut(count).ok_or(())? = x as i32;
+                    count += 1;
+                }
+            }
+            let crossings = &mut crossings[..count];
+            crossings.sort_unstable();
+            for pair in crossings.chunks_exact(2) {
+                self.fill_span(pair[0], pair[1] - 1, y, color);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills the part of a ring around `center` between `inner_radius` and
+    /// `outer_radius`, from `start` degrees over `sweep` degrees, clipped to the frame.
+    ///
+    /// Angles are measured clockwise from the positive x axis. An `inner_radius` of 0
+    /// gives a pie slice, for gauges use the width of the gauge band. A `sweep` of 0
+    /// draws nothing.
+    pub fn fill_arc(
+        &mut self,
+        center: (i32, i32),
+        outer_radius: u16,
+        inner_radius: u16,
+        start: i32,
+        sweep: u32,
+        color: u16,
+    ) {
+        if sweep == 0 {
+            return;
+        }
+        let (outer, inner) = (i64::from(outer_radius), i64::from(inner_radius));
+        let start_vector = direction(start);
+        let end_vector = direction(start + sweep.min(360) as i32);
+        let inside = |dx: i64, dy: i64| {
+            let distance = dx * dx + dy * dy;
+            if distance > outer * outer || distance < inner * inner {
+                return false;
+            }
+            let point = (dx as i32, dy as i32);
+            if sweep >= 360 {
+                true
+            } else if sweep <= 180 {
+                // Points on the line through an edge only count on the ray from the center
+                (cross(start_vector, point) > 0 && cross(point, end_vector) > 0)
+                    || on_ray(start_vector, point)
+                    || on_ray(end_vector, point)
+            } else {
+                !(cross(end_vector, point) > 0 && cross(point, start_vector) > 0)
+            }
+        };
+        // Only visit the part of the bounding square inside the frame
+        let (cx, cy) = (i64::from(center.0), i64::from(center.1));
+        let left = (cx - outer).max(0);
+        let right = (cx + outer).min(i64::from(self.width()) - 1);
+        let top = (cy - outer).max(0);
+        let bottom = (cy + outer).min(i64::from(self.height()) - 1);
+        for y in top..=bottom {
+            let mut run_start = None;
+            for x in left..=right + 1 {
+                match (run_start, x <= right && inside(x - cx, y - cy)) {
+                    (None, true) => run_start = Some(x),
+                    (Some(first), false) => {
+                        self.fill_span(first as i32, (x - 1) as i32, y as i32, color);
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Fills the area of same colored pixels connected to `x`, `y` with `color`.
+    ///
+    /// Returns an error if the area is too intricate to be tracked with 64 pending
+    /// spans, in which case it is only partly filled. Use
+    /// [`flood_fill_with_stack`](Self::flood_fill_with_stack) for such areas.
+    pub fn flood_fill(&mut self, x: u16, y: u16, color: u16) -> Result<(), ()> {
+        self.flood_fill_with_stack(x, y, color, &mut [(0, 0); 64])
+    }
+
+    /// Fills the area of same colored pixels connected to `x`, `y` with `color`, using
+    /// `stack` to keep track of spans still to be filled.
+    ///
+    /// Returns an error if `stack` runs full, in which case the area is only partly
+    /// filled.
+    pub fn flood_fill_with_stack(
+        &mut self,
+        x: u16,
+        y: u16,
+        color: u16,
+        stack: &mut [(u16, u16)],
+    ) -> Result<(), ()> {
+        let target = match self.pixel(x, y) {
+            Some(target) if target != color => target,
+            _ => return Ok(()),
+        };
+        let (width, height) = (self.width(), self.height());
+        let mut pending = 0;
+        push_seed(stack, &mut pending, (x, y))?;
+        while pending > 0 {
+            pending -= 1;
+            let (x, y) = stack[pending];
+            if self.pixel(x, y) != Some(target) {
+                continue;
+            }
+            let row = usize::from(y) * usize::from(width);
+            let pixels = &self.pixels()[row..row + usize::from(width)];
+            let left = pixels[..usize::from(x)]
+                .iter()
+                .rposition(|&pixel| pixel != target)
+                .map_or(0, |edge| edge as u16 + 1);
+            let right = pixels[usize::from(x)..]
+                .iter()
+                .position(|&pixel| pixel != target)
+                .map_or(width, |edge| x + edge as u16);
+            self.fill_rect(left, y, right - left, 1, color);
+
+            // Queue the first pixel of every matching run above and below
+            for neighbour in [y.checked_sub(1), Some(y + 1).filter(|&y| y < height)]
+                .iter()
+                .flatten()
+            {
+                let mut in_run = false;
+                for nx in left..right {
+                    let matches = self.pixel(nx, *neighbour) == Some(target);
+                    if matches && !in_run {
+                        push_seed(stack, &mut pending, (nx, *neighbour))?;
+                    }
+                    in_run = matches;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills the pixels from `x0` to `x1`, both included, on row `y`, clipped to the
+    /// frame.
+    pub(crate) fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: u16) {
+        let x0 = x0.max(0);
+        let x1 = x1.min(i32::from(self.width()) - 1);
+        if x0 > x1 || y < 0 || y >= i32::from(self.height()) {
+            return;
+        }
+        self.fill_rect(x0 as u16, y as u16, (x1 - x0 + 1) as u16, 1, color);
+    }
+
+    /// Draws a line with the dash pattern starting at `phase`, returning the phase at
+    /// its end.
+    fn stroke_segment(
+        &mut self,
+        start: (i32, i32),
+        end: (i32, i32),
+        stroke: &Stroke,
+        mut phase: u32,
+    ) -> u32 {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let major = dx.abs().max(dy.abs());
+        let (wide_dx, wide_dy) = (i64::from(dx), i64::from(dy));
+        let length = isqrt((wide_dx * wide_dx + wide_dy * wide_dy) as u64).max(1) as u32;
+        // Widen the cross section of slanted lines to keep them `width` thick
+        let span = if major == 0 {
+            u32::from(stroke.width)
+        } else {
+            (u32::from(stroke.width) * length + major as u32 / 2) / major as u32
+        }
+        .max(1) as i32;
+
+        let (sx, sy) = (dx.signum(), dy.signum());
+        let (ax, ay) = (dx.abs(), -dy.abs());
+        let (mut x, mut y) = start;
+        let mut error = ax + ay;
+        loop {
+            if stroke.is_drawn(phase) {
+                let low = (span - 1) / 2;
+                let high = span / 2;
+                if ax >= -ay {
+                    for py in y - low..=y + high {
+                        self.fill_span(x, x, py, stroke.color);
+                    }
+                } else {
+                    self.fill_span(x - low, x + high, y, stroke.color);
+                }
+            }
+            if (x, y) == end {
+                break;
+            }
+            phase += 1;
+            let doubled = 2 * error;
+            if doubled >= ay {
+                error += ay;
+                x += sx;
+            }
+            if doubled <= ax {
+                error += ax;
+                y += sy;
+            }
+        }
+        phase
+    }
+}
+
+/// Adds `seed` to the first `pending` entries of `stack`, failing if it is full.
+fn push_seed(stack: &mut [(u16, u16)], pending: &mut usize, seed: (u16, u16)) -> Result<(), ()> {
+    *stack.get_mut(*pending).ok_or(())? = seed;
+    *pending += 1;
+    Ok(())
+}
+
+/// Returns the integer square root of `value`, rounded down.
+fn isqrt(value: u64) -> u64 {
+    let mut bit = 1 << 62;
+    while bit > value {
+        bit >>= 2;
+    }
+    let (mut rest, mut root) = (value, 0);
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+/// Returns the unit vector in direction `degrees`, scaled by 2^14.
+fn direction(degrees: i32) -> (i32, i32) {
+    (sine(degrees + 90), sine(degrees))
+}
+
+/// Returns the sine of `degrees`, scaled by 2^14.
+fn sine(degrees: i32) -> i32 {
+    let degrees = degrees.rem_euclid(360) as usize;
+    match degrees {
+        0..=90 => i32::from(SINE[degrees]),
+        91..=180 => i32::from(SINE[180 - degrees]),
+        181..=270 => -i32::from(SINE[degrees - 180]),
+        _ => -i32::from(SINE[360 - degrees]),
+    }
+}
+
+/// Returns the z component of the cross product of `a` and `b`, which is positive if
+/// `b` is clockwise from `a` on screen.
+fn cross(a: (i32, i32), b: (i32, i32)) -> i64 {
+    i64::from(a.0) * i64::from(b.1) - i64::from(a.1) * i64::from(b.0)
+}
+
+/// Returns whether `point` lies on the ray from the origin in direction `ray`.
+fn on_ray(ray: (i32, i32), point: (i32, i32)) -> bool {
+    cross(ray, point) == 0 && dot(ray, point) >= 0
+}
+
+/// Returns the dot product of `a` and `b`.
+fn dot(a: (i32, i32), b: (i32, i32)) -> i64 {
+    i64::from(a.0) * i64::from(b.0) + i64::from(a.1) * i64::from(b.1)
+}
+
+/// Sine of 0 to 90 degrees, scaled by 2^14.
+static SINE: [u16; 91] = [
+    0, 286, 572, 857, 1143, 1428, 1713, 1997, 2280, 2563, 2845, 3126, 3406, 3686, 3964, 4240, 4516,
+    4790, 5063, 5334, 5604, 5872, 6138, 6402, 6664, 6924, 7182, 7438, 7692, 7943, 8192, 8438, 8682,
+    8923, 9162, 9397, 9630, 9860, 10087, 10311, 10531, 10749, 10963, 11174, 11381, 11585, 11786,
+    11982, 12176, 12365, 12551, 12733, 12911, 13085, 13255, 13421, 13583, 13741, 13894, 14044,
+    14189, 14330, 14466, 14598, 14726, 14849, 14968, 15082, 15191, 15296, 15396, 15491, 15582,
+    15668, 15749, 15826, 15897, 15964, 16026, 16083, 16135, 16182, 16225, 16262, 16294, 16322,
+    16344, 16362, 16374, 16382, 16384,
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::framebuffer::FrameBuffer;
+
+    fn count(frame: &FrameBuffer<'_, [u16; 441]>, color: u16) -> usize {
+        frame
+            .pixels()
+            .iter()
+            .filter(|&&pixel| pixel == color)
+            .count()
+    }
+
+    #[test]
+    fn fill_circle_handles_the_largest_radius() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_circle((10, 10), u16::MAX, 1);
+        assert_eq!(count(&frame, 1), 441);
+
+        // Only the bottom of the circle reaches into the frame
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_circle((10, 10 + 65535), u16::MAX, 1);
+        assert_eq!(frame.pixel(10, 9), Some(0));
+        assert_eq!(frame.pixel(9, 10), Some(0));
+        assert_eq!(frame.pixel(10, 10), Some(1));
+    }
+
+    #[test]
+    fn fill_arc_without_sweep_draws_nothing() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_arc((10, 10), 10, 0, 0, 0, 1);
+        assert_eq!(count(&frame, 1), 0);
+    }
+
+    #[test]
+    fn fill_arc_excludes_the_opposite_ray() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_arc((10, 10), 10, 0, 0, 90, 1);
+        // The start and end edges are drawn from the center outwards only
+        assert_eq!(frame.pixel(20, 10), Some(1));
+        assert_eq!(frame.pixel(10, 20), Some(1));
+        assert_eq!(frame.pixel(0, 10), Some(0));
+        assert_eq!(frame.pixel(10, 0), Some(0));
+        assert_eq!(frame.pixel(5, 5), Some(0));
+        assert_eq!(frame.pixel(15, 15), Some(1));
+    }
+
+    #[test]
+    fn fill_arc_half_includes_the_diameter() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_arc((10, 10), 10, 0, 0, 180, 1);
+        assert_eq!(frame.pixel(0, 10), Some(1));
+        assert_eq!(frame.pixel(20, 10), Some(1));
+        assert_eq!(frame.pixel(10, 5), Some(0));
+    }
+
+    #[test]
+    fn fill_polygon_fills_pixel_centers_inside() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame
+            .fill_polygon(&[(1, 1), (5, 1), (5, 4), (1, 4)], 1)
+            .unwrap();
+        assert_eq!(count(&frame, 1), 12);
+        assert_eq!(frame.pixel(1, 1), Some(1));
+        assert_eq!(frame.pixel(4, 3), Some(1));
+        assert_eq!(frame.pixel(5, 1), Some(0));
+        assert_eq!(frame.pixel(1, 4), Some(0));
+
+        // A polygon sharing the right edge doesn't overlap
+        frame
+            .fill_polygon(&[(5, 1), (9, 1), (9, 4), (5, 4)], 2)
+            .unwrap();
+        assert_eq!(count(&frame, 1), 12);
+        assert_eq!(count(&frame, 2), 12);
+    }
+
+    #[test]
+    fn fill_polygon_is_clipped_to_the_frame() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame
+            .fill_polygon(&[(-10, -10), (30, -10), (30, 30), (-10, 30)], 1)
+            .unwrap();
+        assert_eq!(count(&frame, 1), 441);
+    }
+
+    #[test]
+    fn fill_polygon_rejects_rows_with_too_many_crossings() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        // A comb with 17 teeth crosses row 1 with 34 edges
+        let mut points = [(0, 0); 36];
+        for tooth in 0..17 {
+            points[tooth * 2] = (tooth as i32, 10);
+            points[tooth * 2 + 1] = (tooth as i32, 0);
+        }
+        points[34] = (17, 10);
+        points[35] = (17, 20);
+        assert_eq!(frame.fill_polygon(&points, 1), Err(()));
+        assert_eq!(frame.fill_polygon(&points[..32], 1), Ok(()));
+    }
+
+    #[test]
+    fn fill_arc_handles_the_largest_radius() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_arc((10, 10), u16::MAX, 0, 0, 360, 1);
+        assert_eq!(count(&frame, 1), 441);
+
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.fill_arc((-65535, 10), u16::MAX, 0, 0, 360, 1);
+        assert_eq!(frame.pixel(0, 10), Some(1));
+        assert_eq!(frame.pixel(1, 10), Some(0));
+    }
+
+    #[test]
+    fn flood_fill_stays_inside_an_outline() {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        frame.outline_rect(2, 2, 10, 8, 1);
+        frame.flood_fill(5, 5, 2).unwrap();
+        assert_eq!(count(&frame, 2), 8 * 6);
+        assert_eq!(frame.pixel(0, 0), Some(0));
+
+        // Filling the outside goes around the outline
+        frame.flood_fill(0, 0, 3).unwrap();
+        assert_eq!(count(&frame, 3), 441 - 10 * 8);
+    }
+
+    /// Returns a frame where every other column is a separate run below the first row.
+    fn combs() -> FrameBuffer<'static, [u16; 441]> {
+        let mut frame = FrameBuffer::new([0; 441], 21, 21).unwrap();
+        for x in (1..21).step_by(2) {
+            frame.fill_rect(x, 1, 1, 20, 1);
+        }
+        frame
+    }
+
+    #[test]
+    fn flood_fill_reports_a_full_stack() {
+        let mut frame = combs();
+        assert_eq!(
+            frame.flood_fill_with_stack(0, 0, 2, &mut [(0, 0); 4]),
+            Err(())
+        );
+
+        let mut frame = combs();
+        frame.flood_fill(0, 0, 2).unwrap();
+        assert_eq!(count(&frame, 2), 21 + 11 * 20);
+    }
+}
@@ -0,0 +1,213 @@
+//! Bit-banged SPI over GPIO pins.
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Placeholder for a [`SoftSpi`] without a chip select pin.
+pub struct NoCs;
+
+impl OutputPin for NoCs {
+    type Error = core::convert::Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Write-only SPI in mode 0, most significant bit first, clocked out over GPIO pins.
+///
+/// Use this in place of a hardware SPI peripheral when the display is wired to
+/// arbitrary pins. Each clock phase lasts `half_period_us` microseconds, which can be
+/// raised for long or slow wiring.
+pub struct SoftSpi<SCK, MOSI, CS, DELAY> {
+    sck: SCK,
+    mosi: MOSI,
+    cs: CS,
+    delay: DELAY,
+    half_period_us: u16,
+}
+
+impl<SCK, MOSI, DELAY> SoftSpi<SCK, MOSI, NoCs, DELAY>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    /// Creates a bus on the given clock and data pins.
+    pub fn new(sck: SCK, mosi: MOSI, delay: DELAY, half_period_us: u16) -> Self {
+        SoftSpi {
+            sck,
+            mosi,
+            cs: NoCs,
+            delay,
+            half_period_us,
+        }
+    }
+
+    /// Adds an active-low chip select pin, asserted for the duration of each write.
+    pub fn with_cs<CS>(self, cs: CS) -> SoftSpi<SCK, MOSI, CS, DELAY>
+    where
+        CS: OutputPin,
+    {
+        SoftSpi {
+            sck: self.sck,
+            mosi: self.mosi,
+            cs,
+            delay: self.delay,
+            half_period_us: self.half_period_us,
+        }
+    }
+}
+
+impl<SCK, MOSI, CS, DELAY> SoftSpi<SCK, MOSI, CS, DELAY>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    fn write_byte(&mut self, byte: u8) -> Result<(), ()> {
+        for bit in (0..8).rev() {
+            if byte & (1 << bit) != 0 {
+                self.mosi.set_high().map_err(|_| ())?;
+            } else {
+                self.mosi.set_low().map_err(|_| ())?;
+            }
+            self.delay.delay_us(self.half_period_us);
+            self.sck.set_high().map_err(|_| ())?;
+            self.delay.delay_us(self.half_period_us);
+            self.sck.set_low().map_err(|_| ())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the pins and delay.
+    pub fn release(self) -> (SCK, MOSI, CS, DELAY) {
+        (self.sck, self.mosi, self.cs, self.delay)
+    }
+}
+
+impl<SCK, MOSI, CS, DELAY> spi::Write<u8> for SoftSpi<SCK, MOSI, CS, DELAY>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.sck.set_low().map_err(|_| ())?;
+        self.cs.set_low().map_err(|_| ())?;
+        let result = words.iter().try_for_each(|&byte| self.write_byte(byte));
+        self.cs.set_high().map_err(|_| ())?;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::mock::MockDelay;
+
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use embedded_hal::blocking::spi::Write;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Pin {
+        Sck,
+        Mosi,
+        Cs,
+    }
+
+    /// Appends every level change to a log shared by all pins.
+    struct LogPin(Pin, Rc<RefCell<Vec<(Pin, bool)>>>);
+
+    impl OutputPin for LogPin {
+        type Error = core::convert::Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.1.borrow_mut().push((self.0, false));
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.1.borrow_mut().push((self.0, true));
+            Ok(())
+        }
+    }
+
+    fn write(words: &[u8]) -> Vec<(Pin, bool)> {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut spi = SoftSpi::new(
+            LogPin(Pin::Sck, log.clone()),
+            LogPin(Pin::Mosi, log.clone()),
+            MockDelay,
+            1,
+        )
+        .with_cs(LogPin(Pin::Cs, log.clone()));
+        spi.write(words).unwrap();
+        let log = log.borrow().clone();
+        log
+    }
+
+    /// Returns the data level at each rising clock edge.
+    fn sampled(log: &[(Pin, bool)]) -> Vec<bool> {
+        let mut mosi = false;
+        let mut bits = Vec::new();
+        for &(pin, level) in log {
+            match pin {
+                Pin::Mosi => mosi = level,
+                Pin::Sck if level => bits.push(mosi),
+                _ => {}
+            }
+        }
+        bits
+    }
+
+    #[test]
+    fn bits_are_sent_msb_first() {
+        let log = write(&[0b1010_0001, 0x0F]);
+        let bits: Vec<bool> = [1, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1]
+            .iter()
+            .map(|&bit| bit == 1)
+            .collect();
+        assert_eq!(sampled(&log), bits);
+    }
+
+    #[test]
+    fn data_changes_while_the_clock_is_low() {
+        let log = write(&[0x5A]);
+        let mut sck = true;
+        for &(pin, level) in &log {
+            match pin {
+                Pin::Sck => sck = level,
+                Pin::Mosi => assert!(!sck),
+                Pin::Cs => {}
+            }
+        }
+        assert!(!sck);
+    }
+
+    #[test]
+    fn chip_select_frames_the_write() {
+        let log = write(&[0xFF]);
+        assert_eq!(log[..2], [(Pin::Sck, false), (Pin::Cs, false)]);
+        assert_eq!(log.last(), Some(&(Pin::Cs, true)));
+        assert_eq!(
+            log.iter()
+                .filter(|&&entry| entry == (Pin::Sck, true))
+                .count(),
+            8
+        );
+    }
+}
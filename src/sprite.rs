@@ -0,0 +1,248 @@
+//! Sprites with optional transparency.
+
+use crate::backlight::Backlight;
+use crate::pixels::PixelFormat;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// An image in big-endian RGB565, optionally with a transparent color key.
+///
+/// A sprite can also refer to a tile inside a larger sprite sheet.
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    stride: usize,
+    transparent: Option<u16>,
+}
+
+impl<'a> Sprite<'a> {
+    /// Creates a sprite from `width` by `height` pixels of big-endian RGB565 data.
+    ///
+    /// Returns `None` if the data is too short for the given size.
+    pub fn new(data: &'a [u8], width: u16, height: u16) -> Option<Self> {
+        Self::from_sheet(data, width, 0, 0, width, height)
+    }
+
+    /// Creates a sprite from the `width` by `height` tile at `x`, `y` of a sprite sheet
+    /// that is `sheet_width` pixels wide.
+    ///
+    /// Returns `None` if the tile doesn't fit in the sheet data.
+    pub fn from_sheet(
+        data: &'a [u8],
+        sheet_width: u16,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Option<Self> {
+        if u32::from(x) + u32::from(width) > u32::from(sheet_width) {
+            return None;
+        }
+        let stride = usize::from(sheet_width) * 2;
+        let start = usize::from(y) * stride + usize::from(x) * 2;
+        let end = start + usize::from(height) * stride;
+        if height > 0 && end - stride + usize::from(width) * 2 > data.len() {
+            return None;
+        }
+        Some(Sprite {
+            data: &data[start.min(data.len())..],
+            width,
+            height,
+            stride,
+            transparent: None,
+        })
+    }
+
+    /// Makes pixels of the given RGB565 color transparent.
+    pub fn with_transparent(mut self, color: u16) -> Self {
+        self.transparent = Some(color);
+        self
+    }
+
+    /// Returns the sprite width in pixels.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Returns the sprite height in pixels.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the encoded pixels of one row.
+    fn row(&self, y: u16) -> &'a [u8] {
+        let start = usize::from(y) * self.stride;
+        &self.data[start..start + usize::from(self.width) * 2]
+    }
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Draws a sprite with its top left corner at `x`, `y`, clipped to the display.
+    ///
+    /// Transparent pixels are skipped by drawing each row as separate runs of opaque
+    /// pixels, so the display content behind them is kept.
+    pub fn draw_sprite(&mut self, x: i32, y: i32, sprite: &Sprite) -> Result<(), ()> {
+        let left = (-x).clamp(0, i32::from(sprite.width));
+        let top = (-y).clamp(0, i32::from(sprite.height));
+        let right = (self.width as i32 - x).clamp(left, i32::from(sprite.width));
+        let bottom = (self.height as i32 - y).clamp(top, i32::from(sprite.height));
+        if left == right || top == bottom {
+            return Ok(());
+        }
+
+        let full_rows = left == 0 && right == i32::from(sprite.width);
+        if sprite.transparent.is_none()
+            && full_rows
+            && sprite.stride == usize::from(sprite.width) * 2
+            && self.pixel_format == PixelFormat::Rgb565
+        {
+            let start = top as usize * sprite.stride;
+            let end = bottom as usize * sprite.stride;
+            return self.blit(
+                x as u16,
+                (y + top) as u16,
+                sprite.width,
+                (bottom - top) as u16,
+                &sprite.data[start..end],
+            );
+        }
+
+        for row in top..bottom {
+            let data = sprite.row(row as u16);
+            let data = &data[left as usize * 2..right as usize * 2];
+            let sy = (y + row) as u16;
+            match sprite.transparent {
+                None => self.draw_sprite_run((x + left) as u16, sy, data)?,
+                Some(key) => {
+                    let key = key.to_be_bytes();
+                    let mut start = 0;
+                    for (index, pixel) in data.chunks_exact(2).enumerate() {
+                        if pixel == key {
+                            if start < index {
+                                let sx = (x + left) as u16 + start as u16;
+                                self.draw_sprite_run(sx, sy, &data[start * 2..index * 2])?;
+                            }
+                            start = index + 1;
+                        }
+                    }
+                    let end = data.len() / 2;
+                    if start < end {
+                        let sx = (x + left) as u16 + start as u16;
+                        self.draw_sprite_run(sx, sy, &data[start * 2..])?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a run of RGB565 pixels on a single row.
+    fn draw_sprite_run(&mut self, x: u16, y: u16, data: &[u8]) -> Result<(), ()> {
+        let w = (data.len() / 2) as u16;
+        if self.pixel_format == PixelFormat::Rgb565 {
+            self.blit(x, y, w, 1, data)
+        } else {
+            self.set_pixels_buffered(
+                x,
+                y,
+                x + w - 1,
+                y,
+                data.chunks_exact(2)
+                    .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]])),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::instruction::Instruction;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(recorder.spi(), recorder.dc(), MockPin, true, false, 8, 8)
+    }
+
+    /// Returns the first column, row, last column and pixel data of every memory write.
+    fn runs(recorder: &Recorder) -> Vec<(u16, u16, u16, Vec<u8>)> {
+        let (mut columns, mut row) = ((0, 0), 0);
+        let mut runs: Vec<(u16, u16, u16, Vec<u8>)> = Vec::new();
+        for transaction in recorder.transactions() {
+            match transaction {
+                Transaction::Command(Instruction::CASET, data) => {
+                    columns = (
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                    )
+                }
+                Transaction::Command(Instruction::RASET, data) => {
+                    row = u16::from_be_bytes([data[0], data[1]])
+                }
+                Transaction::Command(Instruction::RAMWR, _) => {
+                    runs.push((columns.0, row, columns.1, Vec::new()))
+                }
+                Transaction::Data(data) => {
+                    if let Some(run) = runs.last_mut() {
+                        run.3.extend(data)
+                    }
+                }
+                _ => {}
+            }
+        }
+        runs
+    }
+
+    const DATA: [u8; 12] = [
+        0x00, 0x01, 0xF8, 0x1F, 0x00, 0x02, 0x00, 0x03, 0xF8, 0x1F, 0xF8, 0x1F,
+    ];
+
+    #[test]
+    fn transparent_pixels_split_rows_into_runs() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let sprite = Sprite::new(&DATA, 3, 2).unwrap().with_transparent(0xF81F);
+        display.draw_sprite(2, 4, &sprite).unwrap();
+        assert_eq!(
+            runs(&recorder),
+            vec![
+                (2, 4, 2, vec![0x00, 0x01]),
+                (4, 4, 4, vec![0x00, 0x02]),
+                (2, 5, 2, vec![0x00, 0x03]),
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_are_clipped_to_the_display() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let sprite = Sprite::new(&DATA, 3, 2).unwrap().with_transparent(0xF81F);
+        display.draw_sprite(-1, 7, &sprite).unwrap();
+        assert_eq!(runs(&recorder), vec![(1, 7, 1, vec![0x00, 0x02])]);
+    }
+
+    #[test]
+    fn opaque_sprites_are_sent_in_one_window() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let sprite = Sprite::new(&DATA, 3, 2).unwrap();
+        display.draw_sprite(0, 0, &sprite).unwrap();
+        assert_eq!(runs(&recorder), vec![(0, 0, 2, DATA.to_vec())]);
+    }
+}
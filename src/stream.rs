@@ -0,0 +1,128 @@
+//! Streaming whole frames to a fixed window, for video playback.
+
+use crate::backlight::Backlight;
+use crate::ST7735;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Sends a series of pre-encoded frames to the same window of the display.
+///
+/// The window is set once when the writer is created, so each frame only costs a
+/// `RAMWR` command on top of its pixel data. `RAMWR` restarts writing at the top left
+/// corner of the window, which keeps frames aligned even if the panel missed a byte.
+pub struct FrameWriter<'a, SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    display: &'a mut ST7735<SPI, DC, RST, BL>,
+    frame_len: usize,
+    pause_us: u32,
+}
+
+impl<'a, SPI, DC, RST, BL> FrameWriter<'a, SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Creates a writer for frames covering the window from `sx`, `sy` to `ex`, `ey`,
+    /// both inclusive.
+    pub fn new(
+        display: &'a mut ST7735<SPI, DC, RST, BL>,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+    ) -> Result<Self, ()> {
+        if ex < sx || ey < sy {
+            return Err(());
+        }
+        let pixels =
+            (usize::from(ex) - usize::from(sx) + 1) * (usize::from(ey) - usize::from(sy) + 1);
+        let frame_len = display.pixel_format().encoded_len(pixels);
+        display.set_address_window(sx, sy, ex, ey)?;
+        Ok(FrameWriter {
+            display,
+            frame_len,
+            pause_us: 0,
+        })
+    }
+
+    /// Returns the number of bytes in a frame, in the pixel format of the display.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Limits [`push_frame_paced`](Self::push_frame_paced) to about `fps` frames per
+    /// second, or removes the limit if `fps` is 0.
+    ///
+    /// Without a clock to measure against, the time a frame takes to send is worked
+    /// out from the SPI clock frequency `spi_hz`, and the rest of the frame period is
+    /// waited out after each frame.
+    pub fn set_frame_rate(&mut self, fps: u32, spi_hz: u32) {
+        if fps == 0 || spi_hz == 0 {
+            self.pause_us = 0;
+            return;
+        }
+        let period_us = 1_000_000 / u64::from(fps);
+        let transfer_us = self.frame_len as u64 * 8 * 1_000_000 / u64::from(spi_hz);
+        self.pause_us = period_us.saturating_sub(transfer_us) as u32;
+    }
+
+    /// Sends a frame, encoded in the pixel format of the display.
+    ///
+    /// Returns an error if `frame` isn't exactly [`frame_len`](Self::frame_len) bytes.
+    pub fn push_frame(&mut self, frame: &[u8]) -> Result<(), ()> {
+        if frame.len() != self.frame_len {
+            return Err(());
+        }
+        self.display.write_raw_slice(frame)
+    }
+
+    /// Sends a frame like [`push_frame`](Self::push_frame), then waits for the rest of
+    /// the frame period set with [`set_frame_rate`](Self::set_frame_rate).
+    pub fn push_frame_paced<DELAY>(&mut self, frame: &[u8], delay: &mut DELAY) -> Result<(), ()>
+    where
+        DELAY: DelayUs<u32>,
+    {
+        self.push_frame(frame)?;
+        if self.pause_us > 0 {
+            delay.delay_us(self.pause_us);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder};
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(
+            recorder.spi(),
+            recorder.dc(),
+            MockPin,
+            true,
+            false,
+            128,
+            160,
+        )
+    }
+
+    #[test]
+    fn frame_len_covers_the_window() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let writer = FrameWriter::new(&mut display, 0, 0, 65535, 1).unwrap();
+        assert_eq!(writer.frame_len(), 65536 * 2 * 2);
+        assert!(FrameWriter::new(&mut display, 1, 0, 0, 0).is_err());
+        assert!(FrameWriter::new(&mut display, 0, 1, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+//! Text drawing with built-in bitmap fonts.
+//!
+//! This covers simple status lines without the embedded-graphics text stack. The
+//! glyphs are taken from the public domain X11 `misc-fixed` fonts and cover printable
+//! ASCII; other characters are drawn as `?`.
+
+use crate::backlight::Backlight;
+use crate::pixels::PixelEncoder;
+use crate::{Orientation, ST7735};
+
+use core::fmt::{self, Write};
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// A monospaced bitmap font of at most 8 by 16 pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Font {
+    width: u8,
+    height: u8,
+    /// One byte per glyph row, most significant bit on the left, for characters
+    /// `' '` to `'~'`.
+    glyphs: &'static [u8],
+}
+
+/// 6 by 8 pixel font, 21 characters per line on a 128 pixel wide display.
+///
+/// Uses the 5x8 glyphs with a column of spacing.
+pub const FONT_6X8: Font = Font {
+    width: 6,
+    height: 8,
+    glyphs: &GLYPHS_6X8,
+};
+
+/// 8 by 16 pixel font, 16 characters per line on a 128 pixel wide display.
+///
+/// Uses the 8x13 glyphs with three rows of line spacing.
+pub const FONT_8X16: Font = Font {
+    width: 8,
+    height: 16,
+    glyphs: &GLYPHS_8X16,
+};
+
+impl Font {
+    /// Returns the width of a character cell in pixels.
+    pub fn width(&self) -> u16 {
+        u16::from(self.width)
+    }
+
+    /// Returns the height of a character cell in pixels.
+    pub fn height(&self) -> u16 {
+        u16::from(self.height)
+    }
+
+    /// Returns the rows of the glyph for `c`.
+    fn glyph(&self, c: char) -> &'static [u8] {
+        let c = if (' '..='~').contains(&c) { c } else { '?' };
+        let height = usize::from(self.height);
+        let start = (c as usize - ' ' as usize) * height;
+        &self.glyphs[start..start + height]
+    }
+}
+
+impl<SPI, DC, RST, BL> ST7735<SPI, DC, RST, BL>
+where
+    SPI: spi::Write<u8>,
+    DC: OutputPin,
+    RST: OutputPin,
+    BL: Backlight,
+{
+    /// Sets a function that is called with the position and contents of all text
+    /// drawn with [`draw_text`](Self::draw_text), or removes it for `None`.
+    ///
+    /// This lets tests and accessibility tools follow what is on screen, by passing the
+    /// text on to a log or serial port, without looking at the pixels.
+    pub fn set_text_mirror(&mut self, mirror: Option<fn(u16, u16, &str)>) {
+        self.text_mirror = mirror;
+    }
+
+    /// Draws `text` with its top left corner at `x`, `y`, in color `fg` on `bg`.
+    ///
+    /// A `'\n'` continues on the next line at `x`. Characters that don't fit
+    /// completely on the display are left out, but are still passed to the text
+    /// mirror.
+    pub fn draw_text(
+        &mut self,
+        x: u16,
+        y: u16,
+        text: &str,
+        font: &Font,
+        fg: u16,
+        bg: u16,
+    ) -> Result<(), ()> {
+        if let Some(mirror) = self.text_mirror {
+            mirror(x, y, text);
+        }
+        let (mut cx, mut cy) = (u32::from(x), u32::from(y));
+        for c in text.chars() {
+            if c == '\n' {
+                cx = u32::from(x);
+                cy += u32::from(font.height);
+                continue;
+            }
+            if cx + u32::from(font.width) <= self.width
+                && cy + u32::from(font.height) <= self.height
+            {
+                self.draw_glyph(cx as u16, cy as u16, font, c, fg, bg)?;
+            }
+            cx += u32::from(font.width);
+        }
+        Ok(())
+    }
+
+    /// Clears the screen and lists the driver configuration on it: crate version,
+    /// controller ID, size, offsets, orientation, pixel format and SPI clock.
+    ///
+    /// `id` is the result of `read_id` when the display can be read from, and
+    /// `spi_hz` the SPI clock frequency set up by the application. This helps when
+    /// supporting devices that can only be looked at.
+    pub fn show_diagnostics(&mut self, id: Option<[u8; 3]>, spi_hz: u32) -> Result<(), ()> {
+        let (width, height) = (self.width, self.height);
+        let (dx, dy) = (self.dx, self.dy);
+        let (orientation, format) = (self.orientation, self.pixel_format);
+        self.fill_rect(0, 0, width as u16, height as u16, 0x0000)?;
+        let mut y = 2;
+        let mut line = |display: &mut Self, args: fmt::Arguments| {
+            let mut text = Line::default();
+            // Lines longer than the buffer are cut off
+            let _ = text.write_fmt(args);
+            display.draw_text(2, y, text.as_str(), &FONT_6X8, 0xFFFF, 0x0000)?;
+            y += FONT_6X8.height() + 2;
+            Ok::<(), ()>(())
+        };
+        line(
+            self,
+            format_args!("st7735-lcd {}", env!("CARGO_PKG_VERSION")),
+        )?;
+        match id {
+            Some([a, b, c]) => line(self, format_args!("ID: {:02X} {:02X} {:02X}", a, b, c))?,
+            None => line(self, format_args!("ID: n/a"))?,
+        }
+        line(self, format_args!("Size: {}x{}", width, height))?;
+        line(self, format_args!("Offset: {},{}", dx, dy))?;
+        // Short names keep every line within the 21 columns of a 128 pixel wide panel
+        let orientation = match orientation {
+            Orientation::Portrait => "Portrait",
+            Orientation::Landscape => "Landscape",
+            Orientation::PortraitSwapped => "Portrait 180",
+            Orientation::LandscapeSwapped => "Landscape 180",
+        };
+        line(self, format_args!("Orient: {}", orientation))?;
+        line(self, format_args!("Format: {:?}", format))?;
+        line(
+            self,
+            format_args!(
+                "SPI: {}.{} MHz",
+                spi_hz / 1_000_000,
+                spi_hz % 1_000_000 / 100_000
+            ),
+        )
+    }
+
+    fn draw_glyph(
+        &mut self,
+        x: u16,
+        y: u16,
+        font: &Font,
+        c: char,
+        fg: u16,
+        bg: u16,
+    ) -> Result<(), ()> {
+        // Large enough for the biggest glyph in RGB666
+        let mut buffer = [0; 8 * 16 * 3];
+        let format = self.pixel_format;
+        let mut write = |data: &[u8]| self.blit(x, y, font.width(), font.height(), data);
+        let mut encoder = PixelEncoder::new(format, &mut buffer);
+        for &row in font.glyph(c) {
+            for bit in 0..font.width {
+                let color = if row & (0x80 >> bit) != 0 { fg } else { bg };
+                encoder.push(color, &mut write)?;
+            }
+        }
+        encoder.finish(&mut write)
+    }
+}
+
+static GLYPHS_6X8: [u8; 760] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x20, 0x20, 0x20, 0x20, 0x00, 0x20, 0x00, // '!'
+    0x00, 0x50, 0x50, 0x50, 0x00, 0x00, 0x00, 0x00, // '"'
+    0x50, 0x50, 0xF8, 0x50, 0xF8, 0x50, 0x50, 0x00, // '#'
+    0x20, 0x70, 0xA0, 0x70, 0x28, 0x70, 0x20, 0x00, // '$'
+    0x00, 0x40, 0x50, 0x20, 0x50, 0x10, 0x00, 0x00, // '%'
+    0x40, 0xA0, 0xA0, 0x40, 0xA0, 0xA0, 0x50, 0x00, // '&'
+    0x00, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // '\''
+    0x00, 0x20, 0x40, 0x40, 0x40, 0x40, 0x20, 0x00, // '('
+    0x00, 0x40, 0x20, 0x20, 0x20, 0x20, 0x40, 0x00, // ')'
+    0x00, 0x00, 0x90, 0x60, 0xF0, 0x60, 0x90, 0x00, // '*'
+    0x00, 0x00, 0x20, 0x20, 0xF8, 0x20, 0x20, 0x00, // '+'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x20, 0x40, // ','
+    0x00, 0x00, 0x00, 0x00, 0xF0, 0x00, 0x00, 0x00, // '-'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x70, 0x20, // '.'
+    0x00, 0x10, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, // '/'
+    0x00, 0x20, 0x50, 0x50, 0x50, 0x50, 0x20, 0x00, // '0'
+    0x00, 0x20, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, // '1'
+    0x00, 0x60, 0x90, 0x10, 0x60, 0x80, 0xF0, 0x00, // '2'
+    0x00, 0xF0, 0x20, 0x60, 0x10, 0x90, 0x60, 0x00, // '3'
+    0x00, 0x20, 0x60, 0xA0, 0xF0, 0x20, 0x20, 0x00, // '4'
+    0x00, 0xF0, 0x80, 0xE0, 0x10, 0x90, 0x60, 0x00, // '5'
+    0x00, 0x60, 0x80, 0xE0, 0x90, 0x90, 0x60, 0x00, // '6'
+    0x00, 0xF0, 0x10, 0x20, 0x20, 0x40, 0x40, 0x00, // '7'
+    0x00, 0x60, 0x90, 0x60, 0x90, 0x90, 0x60, 0x00, // '8'
+    0x00, 0x60, 0x90, 0x90, 0x70, 0x10, 0x60, 0x00, // '9'
+    0x00, 0x00, 0x60, 0x60, 0x00, 0x60, 0x60, 0x00, // ':'
+    0x00, 0x00, 0x30, 0x30, 0x00, 0x30, 0x20, 0x40, // ';'
+    0x00, 0x10, 0x20, 0x40, 0x40, 0x20, 0x10, 0x00, // '<'
+    0x00, 0x00, 0x00, 0xF0, 0x00, 0xF0, 0x00, 0x00, // '='
+    0x00, 0x40, 0x20, 0x10, 0x10, 0x20, 0x40, 0x00, // '>'
+    0x00, 0x20, 0x50, 0x10, 0x20, 0x00, 0x20, 0x00, // '?'
+    0x30, 0x48, 0x98, 0xA8, 0xA8, 0x90, 0x40, 0x30, // '@'
+    0x00, 0x60, 0x90, 0x90, 0xF0, 0x90, 0x90, 0x00, // 'A'
+    0x00, 0xE0, 0x90, 0xE0, 0x90, 0x90, 0xE0, 0x00, // 'B'
+    0x00, 0x60, 0x90, 0x80, 0x80, 0x90, 0x60, 0x00, // 'C'
+    0x00, 0xE0, 0x90, 0x90, 0x90, 0x90, 0xE0, 0x00, // 'D'
+    0x00, 0xF0, 0x80, 0xE0, 0x80, 0x80, 0xF0, 0x00, // 'E'
+    0x00, 0xF0, 0x80, 0xE0, 0x80, 0x80, 0x80, 0x00, // 'F'
+    0x00, 0x60, 0x90, 0x80, 0xB0, 0x90, 0x60, 0x00, // 'G'
+    0x00, 0x90, 0x90, 0xF0, 0x90, 0x90, 0x90, 0x00, // 'H'
+    0x00, 0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, // 'I'
+    0x00, 0x70, 0x20, 0x20, 0x20, 0xA0, 0x40, 0x00, // 'J'
+    0x00, 0x90, 0xA0, 0xC0, 0xA0, 0xA0, 0x90, 0x00, // 'K'
+    0x00, 0x80, 0x80, 0x80, 0x80, 0x80, 0xF0, 0x00, // 'L'
+    0x00, 0x90, 0xF0, 0xF0, 0x90, 0x90, 0x90, 0x00, // 'M'
+    0x00, 0x90, 0xD0, 0xF0, 0xB0, 0xB0, 0x90, 0x00, // 'N'
+    0x00, 0x60, 0x90, 0x90, 0x90, 0x90, 0x60, 0x00, // 'O'
+    0x00, 0xE0, 0x90, 0x90, 0xE0, 0x80, 0x80, 0x00, // 'P'
+    0x00, 0x60, 0x90, 0x90, 0xD0, 0xB0, 0x60, 0x10, // 'Q'
+    0x00, 0xE0, 0x90, 0x90, 0xE0, 0x90, 0x90, 0x00, // 'R'
+    0x00, 0x60, 0x90, 0x40, 0x20, 0x90, 0x60, 0x00, // 'S'
+    0x00, 0x70, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, // 'T'
+    0x00, 0x90, 0x90, 0x90, 0x90, 0x90, 0x60, 0x00, // 'U'
+    0x00, 0x90, 0x90, 0x90, 0x90, 0x60, 0x60, 0x00, // 'V'
+    0x00, 0x90, 0x90, 0x90, 0xF0, 0xF0, 0x90, 0x00, // 'W'
+    0x00, 0x90, 0x90, 0x60, 0x60, 0x90, 0x90, 0x00, // 'X'
+    0x00, 0x88, 0x88, 0x50, 0x20, 0x20, 0x20, 0x00, // 'Y'
+    0x00, 0xF0, 0x10, 0x20, 0x40, 0x80, 0xF0, 0x00, // 'Z'
+    0x00, 0x70, 0x40, 0x40, 0x40, 0x40, 0x70, 0x00, // '['
+    0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x10, 0x00, // '\\'
+    0x00, 0x70, 0x10, 0x10, 0x10, 0x10, 0x70, 0x00, // ']'
+    0x00, 0x20, 0x50, 0x00, 0x00, 0x00, 0x00, 0x00, // '^'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, // '_'
+    0x00, 0x40, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+    0x00, 0x00, 0x00, 0x70, 0x90, 0x90, 0x70, 0x00, // 'a'
+    0x00, 0x80, 0x80, 0xE0, 0x90, 0x90, 0xE0, 0x00, // 'b'
+    0x00, 0x00, 0x00, 0x30, 0x40, 0x40, 0x30, 0x00, // 'c'
+    0x00, 0x10, 0x10, 0x70, 0x90, 0x90, 0x70, 0x00, // 'd'
+    0x00, 0x00, 0x00, 0x60, 0xB0, 0xC0, 0x60, 0x00, // 'e'
+    0x00, 0x20, 0x50, 0x40, 0xE0, 0x40, 0x40, 0x00, // 'f'
+    0x00, 0x00, 0x00, 0x60, 0x90, 0x70, 0x10, 0x60, // 'g'
+    0x00, 0x80, 0x80, 0xE0, 0x90, 0x90, 0x90, 0x00, // 'h'
+    0x00, 0x20, 0x00, 0x60, 0x20, 0x20, 0x70, 0x00, // 'i'
+    0x00, 0x10, 0x00, 0x10, 0x10, 0x10, 0x50, 0x20, // 'j'
+    0x00, 0x80, 0x80, 0x90, 0xE0, 0x90, 0x90, 0x00, // 'k'
+    0x00, 0x60, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, // 'l'
+    0x00, 0x00, 0x00, 0xD0, 0xA8, 0xA8, 0xA8, 0x00, // 'm'
+    0x00, 0x00, 0x00, 0xE0, 0x90, 0x90, 0x90, 0x00, // 'n'
+    0x00, 0x00, 0x00, 0x60, 0x90, 0x90, 0x60, 0x00, // 'o'
+    0x00, 0x00, 0x00, 0xE0, 0x90, 0xE0, 0x80, 0x80, // 'p'
+    0x00, 0x00, 0x00, 0x70, 0x90, 0x70, 0x10, 0x10, // 'q'
+    0x00, 0x00, 0x00, 0xA0, 0xD0, 0x80, 0x80, 0x00, // 'r'
+    0x00, 0x00, 0x00, 0x30, 0x60, 0x10, 0x60, 0x00, // 's'
+    0x00, 0x40, 0x40, 0xE0, 0x40, 0x50, 0x20, 0x00, // 't'
+    0x00, 0x00, 0x00, 0x90, 0x90, 0x90, 0x70, 0x00, // 'u'
+    0x00, 0x00, 0x00, 0x50, 0x50, 0x50, 0x20, 0x00, // 'v'
+    0x00, 0x00, 0x00, 0x88, 0xA8, 0xA8, 0x50, 0x00, // 'w'
+    0x00, 0x00, 0x00, 0x90, 0x60, 0x60, 0x90, 0x00, // 'x'
+    0x00, 0x00, 0x00, 0x90, 0x90, 0x70, 0x90, 0x60, // 'y'
+    0x00, 0x00, 0x00, 0xF0, 0x20, 0x40, 0xF0, 0x00, // 'z'
+    0x30, 0x40, 0x20, 0xC0, 0x20, 0x40, 0x30, 0x00, // '{'
+    0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, // '|'
+    0xC0, 0x20, 0x40, 0x30, 0x40, 0x20, 0xC0, 0x00, // '}'
+    0x00, 0x50, 0xA0, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+];
+
+/// A line of text formatted without allocation, cut off at 32 bytes.
+#[derive(Default)]
+struct Line {
+    buffer: [u8; 32],
+    len: usize,
+}
+
+impl Line {
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[..self.len]).unwrap_or("")
+    }
+}
+
+impl Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = s.len().min(self.buffer.len() - self.len);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.buffer[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        if end < s.len() {
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+#[rustfmt::skip]
+static GLYPHS_8X16: [u8; 1520] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, // '!'
+    0x00, 0x00, 0x00, 0x24, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '"'
+    0x00, 0x00, 0x00, 0x00, 0x24, 0x24, 0x7E, 0x24, 0x7E, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, // '#'
+    0x00, 0x00, 0x00, 0x10, 0x3C, 0x50, 0x50, 0x38, 0x14, 0x14, 0x78, 0x10, 0x00, 0x00, 0x00, 0x00, // '$'
+    0x00, 0x00, 0x00, 0x22, 0x52, 0x24, 0x08, 0x08, 0x10, 0x24, 0x2A, 0x44, 0x00, 0x00, 0x00, 0x00, // '%'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x48, 0x48, 0x30, 0x4A, 0x44, 0x3A, 0x00, 0x00, 0x00, 0x00, // '&'
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '\''
+    0x00, 0x00, 0x00, 0x04, 0x08, 0x08, 0x10, 0x10, 0x10, 0x08, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, // '('
+    0x00, 0x00, 0x00, 0x20, 0x10, 0x10, 0x08, 0x08, 0x08, 0x10, 0x10, 0x20, 0x00, 0x00, 0x00, 0x00, // ')'
+    0x00, 0x00, 0x00, 0x24, 0x18, 0x7E, 0x18, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '*'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x10, 0x7C, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '+'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00, 0x00, 0x00, // ','
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '-'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x00, // '.'
+    0x00, 0x00, 0x00, 0x02, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00, // '/'
+    0x00, 0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x42, 0x42, 0x24, 0x18, 0x00, 0x00, 0x00, 0x00, // '0'
+    0x00, 0x00, 0x00, 0x10, 0x30, 0x50, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // '1'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x02, 0x04, 0x18, 0x20, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // '2'
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x1C, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // '3'
+    0x00, 0x00, 0x00, 0x04, 0x0C, 0x14, 0x24, 0x44, 0x44, 0x7E, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00, // '4'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x5C, 0x62, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // '5'
+    0x00, 0x00, 0x00, 0x1C, 0x20, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // '6'
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x08, 0x10, 0x10, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // '7'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // '8'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x02, 0x04, 0x38, 0x00, 0x00, 0x00, 0x00, // '9'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x00, // ':'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00, 0x00, 0x00, // ';'
+    0x00, 0x00, 0x00, 0x02, 0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, // '<'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '='
+    0x00, 0x00, 0x00, 0x40, 0x20, 0x10, 0x08, 0x04, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00, 0x00, 0x00, // '>'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x02, 0x04, 0x08, 0x08, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, // '?'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x4E, 0x52, 0x56, 0x4A, 0x40, 0x3C, 0x00, 0x00, 0x00, 0x00, // '@'
+    0x00, 0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // 'A'
+    0x00, 0x00, 0x00, 0x78, 0x44, 0x42, 0x44, 0x78, 0x44, 0x42, 0x44, 0x78, 0x00, 0x00, 0x00, 0x00, // 'B'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'C'
+    0x00, 0x00, 0x00, 0x78, 0x44, 0x42, 0x42, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00, 0x00, 0x00, 0x00, // 'D'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // 'E'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, 0x00, // 'F'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x4E, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // 'G'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // 'H'
+    0x00, 0x00, 0x00, 0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // 'I'
+    0x00, 0x00, 0x00, 0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00, 0x00, 0x00, 0x00, // 'J'
+    0x00, 0x00, 0x00, 0x42, 0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // 'K'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // 'L'
+    0x00, 0x00, 0x00, 0x82, 0x82, 0xC6, 0xAA, 0x92, 0x92, 0x82, 0x82, 0x82, 0x00, 0x00, 0x00, 0x00, // 'M'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x62, 0x52, 0x4A, 0x46, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // 'N'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'O'
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, 0x00, // 'P'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x52, 0x4A, 0x3C, 0x02, 0x00, 0x00, 0x00, // 'Q'
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // 'R'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x3C, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'S'
+    0x00, 0x00, 0x00, 0xFE, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // 'T'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'U'
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x44, 0x44, 0x28, 0x28, 0x28, 0x10, 0x00, 0x00, 0x00, 0x00, // 'V'
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x82, 0x82, 0x92, 0x92, 0x92, 0xAA, 0x44, 0x00, 0x00, 0x00, 0x00, // 'W'
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x28, 0x44, 0x82, 0x82, 0x00, 0x00, 0x00, 0x00, // 'X'
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // 'Y'
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // 'Z'
+    0x00, 0x00, 0x00, 0x3C, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x3C, 0x00, 0x00, 0x00, 0x00, // '['
+    0x00, 0x00, 0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x02, 0x00, 0x00, 0x00, 0x00, // '\\'
+    0x00, 0x00, 0x00, 0x78, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x78, 0x00, 0x00, 0x00, 0x00, // ']'
+    0x00, 0x00, 0x00, 0x10, 0x28, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '^'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFE, 0x00, 0x00, 0x00, // '_'
+    0x00, 0x00, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x02, 0x3E, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // 'a'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x62, 0x5C, 0x00, 0x00, 0x00, 0x00, // 'b'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'c'
+    0x00, 0x00, 0x00, 0x02, 0x02, 0x02, 0x3A, 0x46, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // 'd'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x7E, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'e'
+    0x00, 0x00, 0x00, 0x1C, 0x22, 0x20, 0x20, 0x7C, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // 'f'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x44, 0x44, 0x38, 0x40, 0x3C, 0x42, 0x3C, 0x00, 0x00, // 'g'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // 'h'
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // 'i'
+    0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x44, 0x44, 0x38, 0x00, 0x00, // 'j'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x44, 0x48, 0x70, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // 'k'
+    0x00, 0x00, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // 'l'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xEC, 0x92, 0x92, 0x92, 0x92, 0x82, 0x00, 0x00, 0x00, 0x00, // 'm'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // 'n'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 'o'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x62, 0x5C, 0x40, 0x40, 0x40, 0x00, 0x00, // 'p'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x46, 0x42, 0x46, 0x3A, 0x02, 0x02, 0x02, 0x00, 0x00, // 'q'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x22, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // 'r'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x30, 0x0C, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 's'
+    0x00, 0x00, 0x00, 0x00, 0x20, 0x20, 0x7C, 0x20, 0x20, 0x20, 0x22, 0x1C, 0x00, 0x00, 0x00, 0x00, // 't'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x44, 0x44, 0x3A, 0x00, 0x00, 0x00, 0x00, // 'u'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x28, 0x28, 0x10, 0x00, 0x00, 0x00, 0x00, // 'v'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x82, 0x82, 0x92, 0x92, 0xAA, 0x44, 0x00, 0x00, 0x00, 0x00, // 'w'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x00, 0x00, 0x00, 0x00, // 'x'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x42, 0x3C, 0x00, 0x00, // 'y'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x04, 0x08, 0x10, 0x20, 0x7E, 0x00, 0x00, 0x00, 0x00, // 'z'
+    0x00, 0x00, 0x00, 0x0E, 0x10, 0x10, 0x08, 0x30, 0x08, 0x10, 0x10, 0x0E, 0x00, 0x00, 0x00, 0x00, // '{'
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // '|'
+    0x00, 0x00, 0x00, 0x70, 0x08, 0x08, 0x10, 0x0C, 0x10, 0x08, 0x08, 0x70, 0x00, 0x00, 0x00, 0x00, // '}'
+    0x00, 0x00, 0x00, 0x24, 0x54, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+];
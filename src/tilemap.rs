@@ -0,0 +1,301 @@
+//! Tile maps drawn from a sprite sheet, for retro games and map views.
+
+use crate::backlight::Backlight;
+use crate::sprite::Sprite;
+use crate::ST7735;
+
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Equally sized tiles in a sprite sheet of big-endian RGB565 pixels, numbered row by
+/// row from the top left.
+#[derive(Debug, Clone, Copy)]
+pub struct Tileset<'a> {
+    sheet: &'a [u8],
+    sheet_width: u16,
+    tile_width: u16,
+    tile_height: u16,
+}
+
+impl<'a> Tileset<'a> {
+    /// Creates a tileset of `tile_width` by `tile_height` tiles from a sprite sheet
+    /// that is `sheet_width` pixels wide.
+    ///
+    /// Returns `None` if the tiles are empty or wider than the sheet.
+    pub fn new(
+        sheet: &'a [u8],
+        sheet_width: u16,
+        tile_width: u16,
+        tile_height: u16,
+    ) -> Option<Self> {
+        if tile_width == 0 || tile_height == 0 || tile_width > sheet_width {
+            return None;
+        }
+        Some(Tileset {
+            sheet,
+            sheet_width,
+            tile_width,
+            tile_height,
+        })
+    }
+
+    /// Returns the width of a tile in pixels.
+    pub fn tile_width(&self) -> u16 {
+        self.tile_width
+    }
+
+    /// Returns the height of a tile in pixels.
+    pub fn tile_height(&self) -> u16 {
+        self.tile_height
+    }
+
+    /// Returns tile `index`, or `None` if it is outside of the sheet.
+    pub fn tile(&self, index: u16) -> Option<Sprite<'a>> {
+        let columns = self.sheet_width / self.tile_width;
+        Sprite::from_sheet(
+            self.sheet,
+            self.sheet_width,
+            index % columns * self.tile_width,
+            (index / columns).checked_mul(self.tile_height)?,
+            self.tile_width,
+            self.tile_height,
+        )
+    }
+}
+
+/// A map of tile indices, shown `COLS` by `ROWS` tiles at a time.
+///
+/// The map can be larger than the view and scrolled by whole tiles. Each view cell
+/// remembers the tile it shows, so [`draw`](Self::draw) only sends the tiles that
+/// changed through [`set_tile`](Self::set_tile) or scrolling. Tile indices without a
+/// tile in the tileset are filled with the background color, see
+/// [`set_background`](Self::set_background).
+pub struct TileMap<'a, const COLS: usize, const ROWS: usize> {
+    tileset: Tileset<'a>,
+    map: &'a mut [u16],
+    map_width: u16,
+    x: u16,
+    y: u16,
+    scroll: (u16, u16),
+    background: u16,
+    shown: [[Option<u16>; COLS]; ROWS],
+}
+
+impl<'a, const COLS: usize, const ROWS: usize> TileMap<'a, COLS, ROWS> {
+    /// Creates a view with its top left corner at `x`, `y` on the display, of a map
+    /// stored row by row in `map` that is `map_width` tiles wide.
+    ///
+    /// Returns `None` if the map doesn't hold a whole number of rows.
+    pub fn new(
+        tileset: Tileset<'a>,
+        map: &'a mut [u16],
+        map_width: u16,
+        x: u16,
+        y: u16,
+    ) -> Option<Self> {
+        if map_width == 0 || map.len() % usize::from(map_width) != 0 {
+            return None;
+        }
+        Some(TileMap {
+            tileset,
+            map,
+            map_width,
+            x,
+            y,
+            scroll: (0, 0),
+            background: 0,
+            shown: [[None; COLS]; ROWS],
+        })
+    }
+
+    /// Returns the height of the map in tiles.
+    fn map_height(&self) -> u16 {
+        (self.map.len() / usize::from(self.map_width)) as u16
+    }
+
+    /// Returns the tile index at `x`, `y` of the map, or `None` outside of the map.
+    pub fn tile(&self, x: u16, y: u16) -> Option<u16> {
+        if x < self.map_width && y < self.map_height() {
+            Some(self.map[usize::from(y) * usize::from(self.map_width) + usize::from(x)])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the tile index at `x`, `y` of the map. Positions outside of the map are
+    /// ignored.
+    pub fn set_tile(&mut self, x: u16, y: u16, index: u16) {
+        if x < self.map_width && y < self.map_height() {
+            self.map[usize::from(y) * usize::from(self.map_width) + usize::from(x)] = index;
+        }
+    }
+
+    /// Scrolls the view so its top left tile is `x`, `y` of the map, limited to keep
+    /// the view within the map.
+    pub fn scroll_to(&mut self, x: u16, y: u16) {
+        let max_x = self.map_width.saturating_sub(COLS as u16);
+        let max_y = self.map_height().saturating_sub(ROWS as u16);
+        self.scroll = (x.min(max_x), y.min(max_y));
+    }
+
+    /// Returns the map position of the top left tile of the view.
+    pub fn scroll(&self) -> (u16, u16) {
+        self.scroll
+    }
+
+    /// Sets the RGB565 color of cells whose tile index has no tile in the tileset,
+    /// black by default. Cells already drawn keep their color until
+    /// [`invalidate`](Self::invalidate) is called.
+    pub fn set_background(&mut self, color: u16) {
+        self.background = color;
+    }
+
+    /// Makes the next [`draw`](Self::draw) send every tile of the view.
+    pub fn invalidate(&mut self) {
+        self.shown = [[None; COLS]; ROWS];
+    }
+
+    /// Draws the tiles of the view that changed since the last call.
+    pub fn draw<SPI, DC, RST, BL>(
+        &mut self,
+        display: &mut ST7735<SPI, DC, RST, BL>,
+    ) -> Result<(), ()>
+    where
+        SPI: spi::Write<u8>,
+        DC: OutputPin,
+        RST: OutputPin,
+        BL: Backlight,
+    {
+        let (tile_width, tile_height) = (self.tileset.tile_width, self.tileset.tile_height);
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let index = self.tile(
+                    self.scroll.0.saturating_add(col as u16),
+                    self.scroll.1.saturating_add(row as u16),
+                );
+                if index.is_none() || self.shown[row][col] == index {
+                    continue;
+                }
+                let x = i32::from(self.x) + col as i32 * i32::from(tile_width);
+                let y = i32::from(self.y) + row as i32 * i32::from(tile_height);
+                if let Some(sprite) = index.and_then(|index| self.tileset.tile(index)) {
+                    display.draw_sprite(x, y, &sprite)?;
+                } else {
+                    // Clear the cell, clipped to the display
+                    let (width, height) = display.dimensions();
+                    let w = (width as i32 - x).min(i32::from(tile_width));
+                    let h = (height as i32 - y).min(i32::from(tile_height));
+                    if w > 0 && h > 0 {
+                        display.fill_rect(
+                            x as u16,
+                            y as u16,
+                            w as u16,
+                            h as u16,
+                            self.background,
+                        )?;
+                    }
+                }
+                self.shown[row][col] = index;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the map storage.
+    pub fn release(self) -> &'a mut [u16] {
+        self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use crate::instruction::Instruction;
+    use crate::mock::{MockDc, MockPin, MockSpi, Recorder, Transaction};
+
+    use alloc::vec::Vec;
+
+    /// Two 2 by 2 tiles, one above the other.
+    const SHEET: [u8; 16] = [0; 16];
+
+    fn display(recorder: &Recorder) -> ST7735<MockSpi, MockDc, MockPin> {
+        ST7735::new(recorder.spi(), recorder.dc(), MockPin, true, false, 8, 8)
+    }
+
+    /// Returns the top left corner of every memory write.
+    fn writes(recorder: &Recorder) -> Vec<(u16, u16)> {
+        let (mut x, mut y) = (0, 0);
+        let mut writes = Vec::new();
+        for transaction in recorder.transactions() {
+            match transaction {
+                Transaction::Command(Instruction::CASET, data) => {
+                    x = u16::from_be_bytes([data[0], data[1]])
+                }
+                Transaction::Command(Instruction::RASET, data) => {
+                    y = u16::from_be_bytes([data[0], data[1]])
+                }
+                Transaction::Command(Instruction::RAMWR, _) => writes.push((x, y)),
+                _ => {}
+            }
+        }
+        writes
+    }
+
+    #[test]
+    fn only_changed_tiles_are_drawn() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let tileset = Tileset::new(&SHEET, 2, 2, 2).unwrap();
+        let mut map = [0, 1, 0, 1, 0, 1];
+        let mut view: TileMap<2, 2> = TileMap::new(tileset, &mut map, 3, 0, 0).unwrap();
+
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder), [(0, 0), (2, 0), (0, 2), (2, 2)]);
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 4);
+
+        view.set_tile(0, 0, 0);
+        view.set_tile(1, 1, 1);
+        view.set_tile(2, 1, 0);
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[4..], [(2, 2)]);
+
+        view.invalidate();
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 9);
+    }
+
+    #[test]
+    fn scrolling_redraws_cells_showing_other_tiles() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let tileset = Tileset::new(&SHEET, 2, 2, 2).unwrap();
+        let mut map = [0, 1, 1, 0, 1, 1];
+        let mut view: TileMap<2, 2> = TileMap::new(tileset, &mut map, 3, 0, 0).unwrap();
+        view.draw(&mut display).unwrap();
+
+        // Only the left column changes from tile 0 to tile 1
+        view.scroll_to(5, 5);
+        assert_eq!(view.scroll(), (1, 0));
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder)[4..], [(0, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn missing_tiles_are_cleared_once() {
+        let recorder = Recorder::new();
+        let mut display = display(&recorder);
+        let tileset = Tileset::new(&SHEET, 2, 2, 2).unwrap();
+        let mut map = [0, 9];
+        let mut view: TileMap<2, 1> = TileMap::new(tileset, &mut map, 2, 5, 0).unwrap();
+        view.set_background(0xF800);
+
+        // The second cell is clipped to the last column of the display
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder), [(5, 0), (7, 0)]);
+        view.draw(&mut display).unwrap();
+        assert_eq!(writes(&recorder).len(), 2);
+    }
+}